@@ -0,0 +1,229 @@
+use crate::Route;
+use reqwest::Method;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{mpsc as std_mpsc, Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Body fields whose values never reach an audit sink. Matched case-insensitively against
+/// every key in the body, so `password` in `VpsReinstallData` and `VpsChangePasswordData` is
+/// covered as well as a Discord webhook URL.
+const REDACTED_KEYS: [&str; 4] = ["password", "secret", "token", "webhook"];
+
+/// One mutating call, as handed to an [`AuditSink`] after it completed or was dropped.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditRecord {
+    /// When the call completed or was dropped. Serialized as an RFC 3339 UTC timestamp.
+    #[serde(serialize_with = "serialize_timestamp")]
+    pub timestamp: SystemTime,
+    #[serde(serialize_with = "serialize_method")]
+    pub method: Method,
+    /// The service method that sent it, for example `VpsApiService::reinstall`.
+    pub operation: Option<&'static str>,
+    /// The path template, for example `/services/{id}/vps/reinstall`.
+    pub template: String,
+    pub service_id: Option<String>,
+    /// The request body with passwords and other secrets replaced by `***`.
+    pub body: Option<Value>,
+    /// The HTTP status of the final response, if one was received.
+    pub status: Option<u16>,
+    /// [`NodestyError::kind`](crate::NodestyError::kind) of the failure, if the call failed.
+    /// `cancelled` if it was dropped before it completed.
+    pub error: Option<&'static str>,
+    /// Who made the call, see
+    /// [`NodestyApiClientBuilder::audit_actor`](crate::NodestyApiClientBuilder::audit_actor).
+    pub actor: Option<String>,
+}
+
+impl AuditRecord {
+    pub(crate) fn new(
+        method: &Method,
+        route: &Route,
+        body: Option<&Value>,
+        status: Option<u16>,
+        error: Option<&'static str>,
+        actor: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            method: method.clone(),
+            operation: route.operation(),
+            template: route.template().to_string(),
+            service_id: route.service_id().map(str::to_string),
+            body: body.map(redact),
+            status,
+            error,
+            actor,
+        }
+    }
+}
+
+/// Receives an [`AuditRecord`] for every non-GET call made through a client. Register one
+/// with [`NodestyApiClientBuilder::audit_sink`](crate::NodestyApiClientBuilder::audit_sink).
+///
+/// `record` runs on the calling task right after the call completes, or while its future is
+/// dropped if it did not complete, so it must not block: hand the record off instead, as
+/// [`JsonlFileSink`] and [`ChannelSink`] do. A failing sink does not fail the call; the error
+/// is logged with the `tracing` feature.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord) -> io::Result<()>;
+}
+
+/// Appends each record as one line of JSON to a file.
+///
+/// The file is written by a background thread, so `record` only serializes the record and
+/// never waits for the disk. A failed write is returned by the next call to `record`.
+/// Dropping the sink waits until every record handed to it has been written.
+#[derive(Debug)]
+pub struct JsonlFileSink {
+    lines: Option<std_mpsc::Sender<Vec<u8>>>,
+    failure: Arc<Mutex<Option<io::Error>>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl JsonlFileSink {
+    /// Opens `path` for appending, creating it if needed.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let (lines, received) = std_mpsc::channel::<Vec<u8>>();
+        let failure = Arc::new(Mutex::new(None));
+        let failed = failure.clone();
+
+        let writer = thread::Builder::new()
+            .name("nodesty-audit".to_string())
+            .spawn(move || {
+                for line in received {
+                    // One write per record keeps lines intact when several clients append to
+                    // the file.
+                    if let Err(error) = file.write_all(&line).and_then(|()| file.flush()) {
+                        *failed.lock().unwrap() = Some(error);
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            lines: Some(lines),
+            failure,
+            writer: Some(writer),
+        })
+    }
+}
+
+impl AuditSink for JsonlFileSink {
+    fn record(&self, record: &AuditRecord) -> io::Result<()> {
+        if let Some(error) = self.failure.lock().unwrap().take() {
+            return Err(error);
+        }
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.lines
+            .as_ref()
+            .and_then(|lines| lines.send(line).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "audit writer thread stopped"))
+    }
+}
+
+impl Drop for JsonlFileSink {
+    fn drop(&mut self) {
+        // Closing the channel lets the writer finish the queued lines and exit.
+        drop(self.lines.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Sends each record to a channel, for example to ship it to a log pipeline from a task of
+/// your own.
+#[derive(Debug, Clone)]
+pub struct ChannelSink {
+    sender: mpsc::UnboundedSender<AuditRecord>,
+}
+
+impl ChannelSink {
+    pub fn new(sender: mpsc::UnboundedSender<AuditRecord>) -> Self {
+        Self { sender }
+    }
+
+    /// Creates a sink together with the receiving end of its channel.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<AuditRecord>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self::new(sender), receiver)
+    }
+}
+
+impl AuditSink for ChannelSink {
+    fn record(&self, record: &AuditRecord) -> io::Result<()> {
+        self.sender
+            .send(record.clone())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "audit receiver was dropped"))
+    }
+}
+
+fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let key_lower = key.to_ascii_lowercase();
+                    let value = if REDACTED_KEYS.iter().any(|secret| key_lower.contains(secret)) {
+                        Value::String("***".to_string())
+                    } else {
+                        redact(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        other => other.clone(),
+    }
+}
+
+fn serialize_method<S: Serializer>(method: &Method, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(method.as_str())
+}
+
+fn serialize_timestamp<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&Rfc3339(*time))
+}
+
+/// Formats a time as `2024-05-01T12:30:00.123Z`.
+struct Rfc3339(SystemTime);
+
+impl fmt::Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let since_epoch = self.0.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = since_epoch.as_secs();
+        let (days, time) = (seconds / 86_400, seconds % 86_400);
+
+        // Converts days since 1970-01-01 to a civil date (Howard Hinnant's algorithm).
+        let z = days as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            year,
+            month,
+            day,
+            time / 3_600,
+            time % 3_600 / 60,
+            time % 60,
+            since_epoch.subsec_millis()
+        )
+    }
+}
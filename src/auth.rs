@@ -0,0 +1,275 @@
+use crate::config::env_var;
+use crate::models::AccessToken;
+use crate::transport::BoxFuture;
+use crate::NodestyError;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Supplies the access token for every request attempt.
+///
+/// The client calls [`token`](Self::token) before each attempt and [`refresh`](Self::refresh)
+/// once after the API answers 401, replaying the request with the refreshed token. Providers
+/// that fetch tokens from elsewhere should cache them in `token` and only go back to the
+/// source in `refresh`.
+pub trait TokenProvider: Send + Sync {
+    fn token(&self) -> BoxFuture<'_, Result<AccessToken, NodestyError>>;
+
+    /// `rejected` is the token the API answered 401 to. Concurrent requests all refresh the
+    /// same rejected token, so a provider whose cached token already differs from it should
+    /// return that token instead of going back to the source again.
+    fn refresh<'a>(
+        &'a self,
+        rejected: &'a AccessToken,
+    ) -> BoxFuture<'a, Result<AccessToken, NodestyError>> {
+        let _ = rejected;
+        self.token()
+    }
+}
+
+/// Always returns the same token. Used when the client is built from an access token.
+#[derive(Debug, Clone)]
+pub struct StaticTokenProvider {
+    token: AccessToken,
+}
+
+impl StaticTokenProvider {
+    pub fn new(token: impl Into<AccessToken>) -> Self {
+        Self {
+            token: token.into(),
+        }
+    }
+}
+
+impl TokenProvider for StaticTokenProvider {
+    fn token(&self) -> BoxFuture<'_, Result<AccessToken, NodestyError>> {
+        Box::pin(async move { Ok(self.token.clone()) })
+    }
+}
+
+/// Reads the token from an environment variable on every request, so a token the application
+/// itself replaces with `std::env::set_var` is picked up without rebuilding the client. Other
+/// processes cannot change this process's environment; for tokens rotated from outside use
+/// [`FileTokenProvider`] or [`CommandTokenProvider`].
+#[derive(Debug, Clone)]
+pub struct EnvTokenProvider {
+    var: String,
+}
+
+impl EnvTokenProvider {
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+}
+
+impl Default for EnvTokenProvider {
+    /// Reads `NODESTY_API_TOKEN`.
+    fn default() -> Self {
+        Self::new("NODESTY_API_TOKEN")
+    }
+}
+
+impl TokenProvider for EnvTokenProvider {
+    fn token(&self) -> BoxFuture<'_, Result<AccessToken, NodestyError>> {
+        Box::pin(async move {
+            env_var(&self.var)?
+                .map(|token| AccessToken::new(token.trim()))
+                .ok_or_else(|| NodestyError::config(self.var.clone(), "is not set"))
+        })
+    }
+}
+
+/// Reads the token from a file, such as a mounted Kubernetes or Vault secret, and reloads
+/// it whenever the file's modification time changes. The modification time is checked at
+/// most once per `check_interval` (one second by default), so requests in between do not
+/// touch the file system.
+#[derive(Debug)]
+pub struct FileTokenProvider {
+    path: PathBuf,
+    check_interval: Duration,
+    cached: Mutex<Option<CachedFile>>,
+}
+
+#[derive(Debug)]
+struct CachedFile {
+    modified: Option<SystemTime>,
+    checked_at: Instant,
+    token: AccessToken,
+}
+
+impl FileTokenProvider {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            check_interval: Duration::from_secs(1),
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// With `rejected`, the file is reread unless another request already replaced that token.
+    fn load(&self, rejected: Option<&AccessToken>) -> Result<AccessToken, NodestyError> {
+        let mut cached = self.cached.lock().unwrap();
+        let force = match (cached.as_ref(), rejected) {
+            (Some(file), Some(rejected)) if file.token != *rejected => {
+                return Ok(file.token.clone());
+            }
+            (_, rejected) => rejected.is_some(),
+        };
+        if let Some(file) = cached.as_ref()
+            && !force
+            && file.checked_at.elapsed() < self.check_interval
+        {
+            return Ok(file.token.clone());
+        }
+
+        let field = || self.path.display().to_string();
+        let modified = std::fs::metadata(&self.path)
+            .map_err(|error| NodestyError::config(field(), error.to_string()))?
+            .modified()
+            .ok();
+
+        if let Some(file) = cached.as_mut()
+            && !force
+            && modified.is_some()
+            && file.modified == modified
+        {
+            file.checked_at = Instant::now();
+            return Ok(file.token.clone());
+        }
+
+        let token = std::fs::read_to_string(&self.path)
+            .map_err(|error| NodestyError::config(field(), error.to_string()))?;
+        let token = AccessToken::new(token.trim());
+        *cached = Some(CachedFile {
+            modified,
+            checked_at: Instant::now(),
+            token: token.clone(),
+        });
+        Ok(token)
+    }
+}
+
+impl TokenProvider for FileTokenProvider {
+    fn token(&self) -> BoxFuture<'_, Result<AccessToken, NodestyError>> {
+        Box::pin(async move { self.load(None) })
+    }
+
+    fn refresh<'a>(
+        &'a self,
+        rejected: &'a AccessToken,
+    ) -> BoxFuture<'a, Result<AccessToken, NodestyError>> {
+        Box::pin(async move { self.load(Some(rejected)) })
+    }
+}
+
+/// Runs a command, for example `op read op://vault/nodesty/token` or `vault kv get -field=token`,
+/// and uses its trimmed standard output as the token. The output is cached until `ttl`
+/// expires (if set) or the API rejects the token.
+#[derive(Debug)]
+pub struct CommandTokenProvider {
+    program: OsString,
+    args: Vec<OsString>,
+    ttl: Option<Duration>,
+    cached: tokio::sync::Mutex<Option<(Instant, AccessToken)>>,
+}
+
+impl CommandTokenProvider {
+    pub fn new(program: impl Into<OsString>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            ttl: None,
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// With `rejected`, the command is rerun unless another request already replaced that token.
+    async fn load(&self, rejected: Option<&AccessToken>) -> Result<AccessToken, NodestyError> {
+        // Holding the lock while the command runs keeps concurrent requests from each
+        // spawning their own copy of it.
+        let mut cached = self.cached.lock().await;
+        if let Some((fetched_at, token)) = cached.as_ref() {
+            let expired = self.ttl.is_some_and(|ttl| fetched_at.elapsed() >= ttl);
+            if !expired && rejected.is_none_or(|rejected| rejected != token) {
+                return Ok(token.clone());
+            }
+        }
+
+        let token = self.run().await?;
+        *cached = Some((Instant::now(), token.clone()));
+        Ok(token)
+    }
+
+    /// Runs the command on its own thread so a slow credential helper does not block the runtime.
+    async fn run(&self) -> Result<AccessToken, NodestyError> {
+        let field = self.program.to_string_lossy().into_owned();
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(command.output());
+        });
+
+        let output = receiver
+            .await
+            .map_err(|_| NodestyError::config(field.clone(), "the command thread panicked"))?
+            .map_err(|error| NodestyError::config(field.clone(), error.to_string()))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = match stderr.trim() {
+                "" => format!("exited with {}", output.status),
+                stderr => format!("exited with {}: {}", output.status, stderr),
+            };
+            return Err(NodestyError::config(field, message));
+        }
+
+        let token = String::from_utf8(output.stdout)
+            .map_err(|_| NodestyError::config(field.clone(), "printed a token that is not UTF-8"))?;
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(NodestyError::config(field, "printed an empty token"));
+        }
+        Ok(AccessToken::new(token))
+    }
+}
+
+impl TokenProvider for CommandTokenProvider {
+    fn token(&self) -> BoxFuture<'_, Result<AccessToken, NodestyError>> {
+        Box::pin(self.load(None))
+    }
+
+    fn refresh<'a>(
+        &'a self,
+        rejected: &'a AccessToken,
+    ) -> BoxFuture<'a, Result<AccessToken, NodestyError>> {
+        Box::pin(self.load(Some(rejected)))
+    }
+}
@@ -0,0 +1,76 @@
+use crate::blocking::NodestyApiClient;
+use crate::models::{
+    dedicated::{
+        DedicatedServerAction, DedicatedServerDetails, DedicatedServerHardwareComponent,
+        DedicatedServerOsTemplate, DedicatedServerReinstallData, DedicatedServerReinstallStatus,
+        DedicatedServerTask,
+    },
+    ApiResponse,
+};
+use crate::NodestyError;
+use std::sync::Arc;
+
+pub struct DedicatedServerApiService {
+    client: Arc<NodestyApiClient>,
+    inner: crate::DedicatedServerApiService,
+}
+
+impl DedicatedServerApiService {
+    pub fn new(client: Arc<NodestyApiClient>) -> Self {
+        Self {
+            inner: crate::DedicatedServerApiService::new(client.inner()),
+            client,
+        }
+    }
+
+    pub fn perform_action(
+        &self,
+        id: &str,
+        action: DedicatedServerAction,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client.block_on(self.inner.perform_action(id, action))
+    }
+
+    pub fn get_details(
+        &self,
+        id: &str,
+    ) -> Result<ApiResponse<DedicatedServerDetails>, NodestyError> {
+        self.client.block_on(self.inner.get_details(id))
+    }
+
+    pub fn get_hardware_components(
+        &self,
+        id: &str,
+    ) -> Result<ApiResponse<Vec<DedicatedServerHardwareComponent>>, NodestyError> {
+        self.client.block_on(self.inner.get_hardware_components(id))
+    }
+
+    pub fn get_os_templates(
+        &self,
+        id: &str,
+    ) -> Result<ApiResponse<Vec<DedicatedServerOsTemplate>>, NodestyError> {
+        self.client.block_on(self.inner.get_os_templates(id))
+    }
+
+    pub fn get_reinstall_status(
+        &self,
+        id: &str,
+    ) -> Result<ApiResponse<DedicatedServerReinstallStatus>, NodestyError> {
+        self.client.block_on(self.inner.get_reinstall_status(id))
+    }
+
+    pub fn reinstall(
+        &self,
+        id: &str,
+        data: DedicatedServerReinstallData,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client.block_on(self.inner.reinstall(id, data))
+    }
+
+    pub fn get_tasks(
+        &self,
+        id: &str,
+    ) -> Result<ApiResponse<Vec<DedicatedServerTask>>, NodestyError> {
+        self.client.block_on(self.inner.get_tasks(id))
+    }
+}
@@ -0,0 +1,119 @@
+use crate::blocking::NodestyApiClient;
+use crate::models::{
+    firewall::{
+        AttackNotificationSettings, FirewallAttackLog, FirewallCreateRuleData, FirewallReverseDns,
+        FirewallRule, FirewallStatistics,
+    },
+    ApiResponse,
+};
+use crate::NodestyError;
+use std::sync::Arc;
+
+pub struct FirewallApiService {
+    client: Arc<NodestyApiClient>,
+    inner: crate::FirewallApiService,
+}
+
+impl FirewallApiService {
+    pub fn new(client: Arc<NodestyApiClient>) -> Self {
+        Self {
+            inner: crate::FirewallApiService::new(client.inner()),
+            client,
+        }
+    }
+
+    pub fn get_attack_logs(
+        &self,
+        service_id: &str,
+        ip: &str,
+    ) -> Result<ApiResponse<Vec<FirewallAttackLog>>, NodestyError> {
+        self.client
+            .block_on(self.inner.get_attack_logs(service_id, ip))
+    }
+
+    pub fn get_attack_notification_settings(
+        &self,
+        service_id: &str,
+        ip: &str,
+    ) -> Result<ApiResponse<AttackNotificationSettings>, NodestyError> {
+        self.client
+            .block_on(self.inner.get_attack_notification_settings(service_id, ip))
+    }
+
+    pub fn update_attack_notification_settings(
+        &self,
+        service_id: &str,
+        ip: &str,
+        data: AttackNotificationSettings,
+    ) -> Result<ApiResponse<AttackNotificationSettings>, NodestyError> {
+        self.client.block_on(
+            self.inner
+                .update_attack_notification_settings(service_id, ip, data),
+        )
+    }
+
+    pub fn reset_reverse_dns(
+        &self,
+        service_id: &str,
+        ip: &str,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client
+            .block_on(self.inner.reset_reverse_dns(service_id, ip))
+    }
+
+    pub fn get_reverse_dns(
+        &self,
+        service_id: &str,
+        ip: &str,
+    ) -> Result<ApiResponse<FirewallReverseDns>, NodestyError> {
+        self.client
+            .block_on(self.inner.get_reverse_dns(service_id, ip))
+    }
+
+    pub fn upsert_reverse_dns(
+        &self,
+        service_id: &str,
+        ip: &str,
+        data: FirewallReverseDns,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client
+            .block_on(self.inner.upsert_reverse_dns(service_id, ip, data))
+    }
+
+    pub fn delete_rule(
+        &self,
+        service_id: &str,
+        ip: &str,
+        rule_id: u32,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client
+            .block_on(self.inner.delete_rule(service_id, ip, rule_id))
+    }
+
+    pub fn get_rules(
+        &self,
+        service_id: &str,
+        ip: &str,
+    ) -> Result<ApiResponse<Vec<FirewallRule>>, NodestyError> {
+        self.client.block_on(self.inner.get_rules(service_id, ip))
+    }
+
+    pub fn create_rule(
+        &self,
+        service_id: &str,
+        ip: &str,
+        data: FirewallCreateRuleData,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client
+            .block_on(self.inner.create_rule(service_id, ip, data))
+    }
+
+    pub fn get_statistics(
+        &self,
+        service_id: &str,
+        ip: &str,
+    ) -> Result<ApiResponse<Vec<FirewallStatistics>>, NodestyError> {
+        self.client
+            .block_on(self.inner.get_statistics(service_id, ip))
+    }
+}
@@ -0,0 +1,84 @@
+//! Synchronous mirrors of the API services for programs that do not run an async runtime.
+//!
+//! Every call blocks the current thread on a private single-threaded tokio runtime, so these
+//! types must not be used from inside an async context.
+
+#[cfg(feature = "dedicated")]
+pub mod dedicated;
+#[cfg(feature = "firewall")]
+pub mod firewall;
+#[cfg(feature = "user")]
+pub mod user;
+#[cfg(feature = "vps")]
+pub mod vps;
+
+#[cfg(feature = "dedicated")]
+pub use dedicated::DedicatedServerApiService;
+#[cfg(feature = "firewall")]
+pub use firewall::FirewallApiService;
+#[cfg(feature = "user")]
+pub use user::UserApiService;
+#[cfg(feature = "vps")]
+pub use vps::VpsApiService;
+
+use crate::models::RestClientOptions;
+use crate::{ApiResponse, NodestyError, Route};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+use std::future::IntoFuture;
+use std::sync::Arc;
+use tokio::runtime::{Builder, Runtime};
+
+/// A blocking wrapper around [`crate::NodestyApiClient`].
+pub struct NodestyApiClient {
+    inner: Arc<crate::NodestyApiClient>,
+    runtime: Runtime,
+}
+
+impl fmt::Debug for NodestyApiClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodestyApiClient")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NodestyApiClient {
+    pub fn new(options: RestClientOptions) -> Result<Self, NodestyError> {
+        Self::from_client(crate::NodestyApiClient::new(options)?)
+    }
+
+    /// Wraps a client built with [`crate::NodestyApiClient::builder`], keeping its transport,
+    /// middleware and other settings.
+    pub fn from_client(client: crate::NodestyApiClient) -> Result<Self, NodestyError> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|error| NodestyError::config("runtime", error.to_string()))?;
+
+        Ok(Self {
+            inner: Arc::new(client),
+            runtime,
+        })
+    }
+
+    /// The async client the blocking calls are executed on.
+    pub fn inner(&self) -> Arc<crate::NodestyApiClient> {
+        self.inner.clone()
+    }
+
+    pub fn send_request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        route: impl Into<Route>,
+        body: Option<Value>,
+    ) -> Result<ApiResponse<T>, NodestyError> {
+        self.block_on(self.inner.send_request(method, route, body))
+    }
+
+    pub(crate) fn block_on<F: IntoFuture>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future.into_future())
+    }
+}
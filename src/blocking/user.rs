@@ -0,0 +1,53 @@
+use crate::blocking::NodestyApiClient;
+use crate::models::{
+    user::{Invoice, Service, Session, Ticket, User, UserInvoiceSummary, UserTicketSummary},
+    ApiResponse,
+};
+use crate::NodestyError;
+use std::sync::Arc;
+
+pub struct UserApiService {
+    client: Arc<NodestyApiClient>,
+    inner: crate::UserApiService,
+}
+
+impl UserApiService {
+    pub fn new(client: Arc<NodestyApiClient>) -> Self {
+        Self {
+            inner: crate::UserApiService::new(client.inner()),
+            client,
+        }
+    }
+
+    pub fn get_services(&self) -> Result<ApiResponse<Vec<Service>>, NodestyError> {
+        self.client.block_on(self.inner.get_services())
+    }
+
+    pub fn get_ticket_by_id(&self, ticket_id: &str) -> Result<ApiResponse<Ticket>, NodestyError> {
+        self.client.block_on(self.inner.get_ticket_by_id(ticket_id))
+    }
+
+    pub fn get_tickets(&self) -> Result<ApiResponse<Vec<UserTicketSummary>>, NodestyError> {
+        self.client.block_on(self.inner.get_tickets())
+    }
+
+    pub fn get_current_user(&self) -> Result<ApiResponse<User>, NodestyError> {
+        self.client.block_on(self.inner.get_current_user())
+    }
+
+    pub fn get_invoice_by_id(
+        &self,
+        invoice_id: &str,
+    ) -> Result<ApiResponse<Invoice>, NodestyError> {
+        self.client
+            .block_on(self.inner.get_invoice_by_id(invoice_id))
+    }
+
+    pub fn get_invoices(&self) -> Result<ApiResponse<Vec<UserInvoiceSummary>>, NodestyError> {
+        self.client.block_on(self.inner.get_invoices())
+    }
+
+    pub fn get_sessions(&self) -> Result<ApiResponse<Vec<Session>>, NodestyError> {
+        self.client.block_on(self.inner.get_sessions())
+    }
+}
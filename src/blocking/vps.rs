@@ -0,0 +1,79 @@
+use crate::blocking::NodestyApiClient;
+use crate::models::{
+    vps::{
+        VpsAction, VpsBackup, VpsChangePasswordData, VpsDetails, VpsGraphs, VpsOsTemplate,
+        VpsReinstallData, VpsTask,
+    },
+    ApiResponse,
+};
+use crate::NodestyError;
+use std::sync::Arc;
+
+pub struct VpsApiService {
+    client: Arc<NodestyApiClient>,
+    inner: crate::VpsApiService,
+}
+
+impl VpsApiService {
+    pub fn new(client: Arc<NodestyApiClient>) -> Self {
+        Self {
+            inner: crate::VpsApiService::new(client.inner()),
+            client,
+        }
+    }
+
+    pub fn perform_action(
+        &self,
+        id: &str,
+        action: VpsAction,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client.block_on(self.inner.perform_action(id, action))
+    }
+
+    pub fn restore_backup(
+        &self,
+        id: &str,
+        data: &VpsBackup,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client.block_on(self.inner.restore_backup(id, data))
+    }
+
+    pub fn get_backups(&self, id: &str) -> Result<ApiResponse<Vec<VpsBackup>>, NodestyError> {
+        self.client.block_on(self.inner.get_backups(id))
+    }
+
+    pub fn change_password(
+        &self,
+        id: &str,
+        data: VpsChangePasswordData,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client.block_on(self.inner.change_password(id, data))
+    }
+
+    pub fn get_usage_statistics(&self, id: &str) -> Result<ApiResponse<VpsGraphs>, NodestyError> {
+        self.client.block_on(self.inner.get_usage_statistics(id))
+    }
+
+    pub fn get_details(&self, id: &str) -> Result<ApiResponse<VpsDetails>, NodestyError> {
+        self.client.block_on(self.inner.get_details(id))
+    }
+
+    pub fn get_os_templates(
+        &self,
+        id: &str,
+    ) -> Result<ApiResponse<Vec<VpsOsTemplate>>, NodestyError> {
+        self.client.block_on(self.inner.get_os_templates(id))
+    }
+
+    pub fn reinstall(
+        &self,
+        id: &str,
+        data: VpsReinstallData,
+    ) -> Result<ApiResponse<()>, NodestyError> {
+        self.client.block_on(self.inner.reinstall(id, data))
+    }
+
+    pub fn get_tasks(&self, id: &str) -> Result<ApiResponse<Vec<VpsTask>>, NodestyError> {
+        self.client.block_on(self.inner.get_tasks(id))
+    }
+}
@@ -0,0 +1,343 @@
+use crate::transport::HttpResponse;
+use crate::Route;
+use reqwest::header::{HeaderValue, CACHE_CONTROL, ETAG};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Settings for the opt-in response cache, see
+/// [`RestClientOptions::with_cache`](crate::RestClientOptions::with_cache).
+///
+/// Successful GET responses are kept for the TTL of their route template, falling back to
+/// `default_ttl`. Once an entry is stale it is revalidated with `If-None-Match` if the API
+/// sent an `ETag`. Any other call on a service drops that service's entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheConfig {
+    pub default_ttl: Duration,
+    /// TTLs keyed by route template, for example `/services/{id}/vps/os-templates`.
+    /// A zero TTL keeps that endpoint out of the cache.
+    pub ttls: HashMap<String, Duration>,
+    pub max_entries: usize,
+}
+
+impl CacheConfig {
+    pub fn new(default_ttl: Duration) -> Self {
+        Self {
+            default_ttl,
+            ttls: HashMap::new(),
+            max_entries: 1024,
+        }
+    }
+
+    pub fn with_ttl(mut self, template: impl Into<String>, ttl: Duration) -> Self {
+        self.ttls.insert(template.into(), ttl);
+        self
+    }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    fn ttl(&self, template: &str) -> Duration {
+        self.ttls.get(template).copied().unwrap_or(self.default_ttl)
+    }
+}
+
+/// What the cache holds for a request that is about to be sent.
+pub(crate) enum Lookup {
+    /// A fresh response that can be returned as is.
+    Fresh(HttpResponse),
+    /// A stale response that can be revalidated with its ETag.
+    Stale {
+        response: HttpResponse,
+        etag: HeaderValue,
+    },
+    Miss,
+}
+
+/// Counts the invalidations a cached response could be affected by. A response fetched under
+/// an older generation may predate a mutation and is not stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Generation {
+    all: u64,
+    service: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct ResponseCache {
+    config: CacheConfig,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<String, Entry>,
+    /// Bumped by [`ResponseCache::clear`].
+    generation: u64,
+    /// Bumped per service by [`ResponseCache::invalidate_service`].
+    service_generations: HashMap<String, u64>,
+}
+
+impl State {
+    fn generation(&self, route: &Route) -> Generation {
+        Generation {
+            all: self.generation,
+            service: route
+                .service_id()
+                .and_then(|service_id| self.service_generations.get(service_id))
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    response: HttpResponse,
+    etag: Option<HeaderValue>,
+    service_id: Option<String>,
+    stored_at: Instant,
+    expires_at: Instant,
+}
+
+impl ResponseCache {
+    pub(crate) fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    /// Also returns the generation to hand back to [`store`](Self::store) and
+    /// [`revalidated`](Self::revalidated) once the response arrives.
+    pub(crate) fn lookup(&self, route: &Route) -> (Lookup, Generation) {
+        let state = self.state.lock().unwrap();
+        let lookup = match state.entries.get(&key(route)) {
+            Some(entry) if entry.expires_at > Instant::now() => Lookup::Fresh(entry.response.clone()),
+            Some(Entry {
+                response,
+                etag: Some(etag),
+                ..
+            }) => Lookup::Stale {
+                response: response.clone(),
+                etag: etag.clone(),
+            },
+            _ => Lookup::Miss,
+        };
+        (lookup, state.generation(route))
+    }
+
+    /// Handles a 304 answer to a revalidation: the stored response is fresh again, unless the
+    /// service was invalidated while the request was in flight.
+    pub(crate) fn revalidated(&self, route: &Route, generation: Generation) -> Option<HttpResponse> {
+        let mut state = self.state.lock().unwrap();
+        if state.generation(route) != generation {
+            return None;
+        }
+        let entry = state.entries.get_mut(&key(route))?;
+        let now = Instant::now();
+        entry.stored_at = now;
+        entry.expires_at = now + self.config.ttl(route.template());
+        Some(entry.response.clone())
+    }
+
+    /// Skipped if the service was invalidated since `generation` was looked up, as the
+    /// response may no longer reflect a mutation that completed in the meantime.
+    pub(crate) fn store(&self, route: &Route, response: &HttpResponse, generation: Generation) {
+        let ttl = self.config.ttl(route.template());
+        if ttl.is_zero() || self.config.max_entries == 0 || !response.status.is_success() {
+            return;
+        }
+        let no_store = response
+            .headers
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.to_ascii_lowercase().contains("no-store"));
+        if no_store {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if state.generation(route) != generation {
+            return;
+        }
+        let entries = &mut state.entries;
+        if entries.len() >= self.config.max_entries {
+            entries.retain(|_, entry| entry.expires_at > now || entry.etag.is_some());
+        }
+        while entries.len() >= self.config.max_entries {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+
+        entries.insert(
+            key(route),
+            Entry {
+                response: response.clone(),
+                etag: response.headers.get(ETAG).cloned(),
+                service_id: route.service_id().map(str::to_string),
+                stored_at: now,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    /// Drops everything cached for the service a mutating call touched, or the whole cache
+    /// if the call was not tied to a service.
+    pub(crate) fn invalidate(&self, route: &Route) {
+        match route.service_id() {
+            Some(service_id) => self.invalidate_service(service_id),
+            None => self.clear(),
+        }
+    }
+
+    pub(crate) fn invalidate_service(&self, service_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .entries
+            .retain(|_, entry| entry.service_id.as_deref() != Some(service_id));
+        *state.service_generations.entry(service_id.to_string()).or_default() += 1;
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.clear();
+        state.generation += 1;
+    }
+}
+
+fn key(route: &Route) -> String {
+    format!("GET {}", route.path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+    use reqwest::StatusCode;
+
+    fn route(id: &str) -> Route {
+        Route::new("VpsApiService::get_details", "/services/{id}/vps/info").param("id", id)
+    }
+
+    fn response(etag: Option<&'static str>, cache_control: Option<&'static str>) -> HttpResponse {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = etag {
+            headers.insert(ETAG, HeaderValue::from_static(etag));
+        }
+        if let Some(cache_control) = cache_control {
+            headers.insert(CACHE_CONTROL, HeaderValue::from_static(cache_control));
+        }
+        HttpResponse {
+            status: StatusCode::OK,
+            headers,
+            body: b"{}".to_vec(),
+        }
+    }
+
+    /// Looks up and stores right away, as a request that nothing raced with.
+    fn store(cache: &ResponseCache, route: &Route, response: &HttpResponse) {
+        let (_, generation) = cache.lookup(route);
+        cache.store(route, response, generation);
+    }
+
+    fn lookup(cache: &ResponseCache, route: &Route) -> Lookup {
+        cache.lookup(route).0
+    }
+
+    #[test]
+    fn fresh_entries_are_served_and_stale_ones_revalidated() {
+        let cache = ResponseCache::new(CacheConfig::new(Duration::from_millis(20)));
+        store(&cache, &route("1"), &response(Some("\"v1\""), None));
+        assert!(matches!(lookup(&cache, &route("1")), Lookup::Fresh(_)));
+        assert!(matches!(lookup(&cache, &route("2")), Lookup::Miss));
+
+        std::thread::sleep(Duration::from_millis(30));
+        let (found, generation) = cache.lookup(&route("1"));
+        match found {
+            Lookup::Stale { response, etag } => {
+                assert_eq!(etag, "\"v1\"");
+                assert_eq!(response.body, b"{}");
+            }
+            _ => panic!("expected a stale entry"),
+        }
+        assert!(cache.revalidated(&route("1"), generation).is_some());
+        assert!(matches!(lookup(&cache, &route("1")), Lookup::Fresh(_)));
+    }
+
+    #[test]
+    fn stale_entries_without_etag_are_misses() {
+        let cache = ResponseCache::new(CacheConfig::new(Duration::from_millis(1)));
+        store(&cache, &route("1"), &response(None, None));
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(matches!(lookup(&cache, &route("1")), Lookup::Miss));
+    }
+
+    #[test]
+    fn uncacheable_responses_are_not_stored() {
+        let config = CacheConfig::new(Duration::from_secs(60))
+            .with_ttl("/services/{id}/vps/info", Duration::ZERO);
+        let cache = ResponseCache::new(config);
+        store(&cache, &route("1"), &response(None, None));
+        assert!(matches!(lookup(&cache, &route("1")), Lookup::Miss));
+
+        let cache = ResponseCache::new(CacheConfig::new(Duration::from_secs(60)));
+        store(&cache, &route("1"), &response(None, Some("private, no-store")));
+        assert!(matches!(lookup(&cache, &route("1")), Lookup::Miss));
+    }
+
+    #[test]
+    fn invalidation_only_drops_the_touched_service() {
+        let cache = ResponseCache::new(CacheConfig::new(Duration::from_secs(60)));
+        store(&cache, &route("1"), &response(None, None));
+        store(&cache, &route("2"), &response(None, None));
+
+        cache.invalidate(&Route::new("VpsApiService::reinstall", "/services/{id}/vps/reinstall").param("id", "1"));
+        let (found, generation) = cache.lookup(&route("1"));
+        assert!(matches!(found, Lookup::Miss));
+        assert!(matches!(lookup(&cache, &route("2")), Lookup::Fresh(_)));
+        assert!(cache.revalidated(&route("1"), generation).is_none());
+
+        cache.invalidate(&Route::from("/tickets"));
+        assert!(matches!(lookup(&cache, &route("2")), Lookup::Miss));
+    }
+
+    #[test]
+    fn responses_fetched_before_an_invalidation_are_not_stored() {
+        let cache = ResponseCache::new(CacheConfig::new(Duration::from_secs(60)));
+        let (_, before) = cache.lookup(&route("1"));
+        let (_, other) = cache.lookup(&route("2"));
+
+        cache.invalidate_service("1");
+        cache.store(&route("1"), &response(None, None), before);
+        cache.store(&route("2"), &response(None, None), other);
+        assert!(matches!(lookup(&cache, &route("1")), Lookup::Miss));
+        assert!(matches!(lookup(&cache, &route("2")), Lookup::Fresh(_)));
+
+        let (_, before) = cache.lookup(&route("2"));
+        cache.clear();
+        cache.store(&route("2"), &response(None, None), before);
+        assert!(matches!(lookup(&cache, &route("2")), Lookup::Miss));
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_first() {
+        let cache = ResponseCache::new(CacheConfig::new(Duration::from_secs(60)).with_max_entries(2));
+        for id in ["1", "2", "3"] {
+            store(&cache, &route(id), &response(None, None));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(matches!(lookup(&cache, &route("1")), Lookup::Miss));
+        assert!(matches!(lookup(&cache, &route("2")), Lookup::Fresh(_)));
+        assert!(matches!(lookup(&cache, &route("3")), Lookup::Fresh(_)));
+    }
+}
@@ -0,0 +1,387 @@
+use crate::{NodestyError, Route};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Settings for the circuit breaker, see
+/// [`RestClientOptions::with_circuit_breaker`](crate::RestClientOptions::with_circuit_breaker).
+///
+/// Each endpoint group (the service a call belongs to, such as `VpsApiService`) has its own
+/// circuit. After `failure_threshold` consecutive failed attempts (connection errors,
+/// timeouts and 5xx responses) the circuit opens and calls fail with
+/// [`NodestyError::CircuitOpen`] without reaching the network. After `open_duration` it
+/// half-opens and lets `half_open_max_calls` trial calls through: a success closes it again,
+/// a failure reopens it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub open_duration: Duration,
+    pub half_open_max_calls: u32,
+}
+
+impl CircuitBreakerConfig {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_duration,
+            half_open_max_calls: 1,
+        }
+    }
+
+    pub fn with_half_open_max_calls(mut self, half_open_max_calls: u32) -> Self {
+        self.half_open_max_calls = half_open_max_calls;
+        self
+    }
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self::new(5, Duration::from_secs(30))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A circuit changed state. Delivered to listeners registered with
+/// [`NodestyApiClientBuilder::on_circuit_change`](crate::NodestyApiClientBuilder::on_circuit_change).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitEvent {
+    pub group: String,
+    pub from: CircuitState,
+    pub to: CircuitState,
+}
+
+pub(crate) type CircuitListener = Arc<dyn Fn(&CircuitEvent) + Send + Sync>;
+
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuits: Mutex<HashMap<String, Circuit>>,
+    notify: Box<dyn Fn(&CircuitEvent) + Send + Sync>,
+}
+
+impl fmt::Debug for CircuitBreaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CircuitBreaker")
+            .field("config", &self.config)
+            .field("circuits", &self.circuits)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+    trials: u32,
+    /// Bumped on every state change, so outcomes of attempts let through under an earlier
+    /// state can be told apart.
+    generation: u64,
+}
+
+impl Circuit {
+    fn transition(&mut self, group: &str, to: CircuitState) -> Option<CircuitEvent> {
+        let from = std::mem::replace(&mut self.state, to);
+        if from != to {
+            self.generation += 1;
+        }
+        (from != to).then(|| CircuitEvent {
+            group: group.to_string(),
+            from,
+            to,
+        })
+    }
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(
+        config: CircuitBreakerConfig,
+        notify: impl Fn(&CircuitEvent) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            config,
+            circuits: Mutex::new(HashMap::new()),
+            notify: Box::new(notify),
+        }
+    }
+
+    pub(crate) fn state(&self, group: &str) -> CircuitState {
+        self.circuits
+            .lock()
+            .unwrap()
+            .get(group)
+            .map_or(CircuitState::Closed, |circuit| circuit.state)
+    }
+
+    /// Lets one attempt through, or fails fast while the group's circuit is open.
+    pub(crate) fn acquire(&self, group: &str) -> Result<CircuitPermit<'_>, NodestyError> {
+        let (result, event) = {
+            let mut circuits = self.circuits.lock().unwrap();
+            let circuit = circuits.entry(group.to_string()).or_insert(Circuit {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: Instant::now(),
+                trials: 0,
+                generation: 0,
+            });
+
+            let mut event = None;
+            if circuit.state == CircuitState::Open
+                && circuit.opened_at.elapsed() >= self.config.open_duration
+            {
+                circuit.trials = 0;
+                event = circuit.transition(group, CircuitState::HalfOpen);
+            }
+
+            let result = match circuit.state {
+                CircuitState::Closed => Ok(false),
+                CircuitState::HalfOpen if circuit.trials < self.config.half_open_max_calls => {
+                    circuit.trials += 1;
+                    Ok(true)
+                }
+                state => Err(NodestyError::CircuitOpen {
+                    group: group.to_string(),
+                    retry_after: match state {
+                        CircuitState::Open => {
+                            Some(self.config.open_duration.saturating_sub(circuit.opened_at.elapsed()))
+                        }
+                        _ => None,
+                    },
+                }),
+            };
+            (result.map(|trial| (trial, circuit.generation)), event)
+        };
+
+        if let Some(event) = event {
+            (self.notify)(&event);
+        }
+        result.map(|(trial, generation)| CircuitPermit {
+            breaker: self,
+            group: group.to_string(),
+            trial,
+            generation,
+            recorded: false,
+        })
+    }
+}
+
+/// One attempt let through by the breaker. Dropping it without recording an outcome, for
+/// example when the call is cancelled, gives a half-open trial slot back.
+///
+/// The outcome only counts if the circuit is still in the state the permit was issued
+/// under: a slow call let through while closed must not close a circuit that opened in the
+/// meantime, and a trial that lost the race to another trial changes nothing.
+pub(crate) struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    group: String,
+    trial: bool,
+    generation: u64,
+    recorded: bool,
+}
+
+impl CircuitPermit<'_> {
+    pub(crate) fn record(mut self, success: bool) {
+        self.recorded = true;
+        let config = &self.breaker.config;
+
+        let event = {
+            let mut circuits = self.breaker.circuits.lock().unwrap();
+            let Some(circuit) = circuits
+                .get_mut(&self.group)
+                .filter(|circuit| circuit.generation == self.generation)
+            else {
+                return;
+            };
+            if self.trial {
+                circuit.trials = circuit.trials.saturating_sub(1);
+            }
+
+            match (circuit.state, success) {
+                (CircuitState::Closed, true) => {
+                    circuit.failures = 0;
+                    None
+                }
+                (CircuitState::HalfOpen, true) => {
+                    circuit.failures = 0;
+                    circuit.transition(&self.group, CircuitState::Closed)
+                }
+                (CircuitState::Closed, false) => {
+                    circuit.failures += 1;
+                    if circuit.failures >= config.failure_threshold {
+                        circuit.opened_at = Instant::now();
+                        circuit.transition(&self.group, CircuitState::Open)
+                    } else {
+                        None
+                    }
+                }
+                (CircuitState::HalfOpen, false) => {
+                    circuit.opened_at = Instant::now();
+                    circuit.transition(&self.group, CircuitState::Open)
+                }
+                (CircuitState::Open, _) => None,
+            }
+        };
+
+        if let Some(event) = event {
+            (self.breaker.notify)(&event);
+        }
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        if self.trial && !self.recorded {
+            let mut circuits = self.breaker.circuits.lock().unwrap();
+            if let Some(circuit) = circuits.get_mut(&self.group)
+                && circuit.generation == self.generation
+            {
+                circuit.trials = circuit.trials.saturating_sub(1);
+            }
+        }
+    }
+}
+
+/// The endpoint group of a route: the service in its operation name (`VpsApiService`), or
+/// the first path segment for routes built from plain strings.
+pub(crate) fn group(route: &Route) -> &str {
+    match route.operation() {
+        Some(operation) => operation.split("::").next().unwrap_or(operation),
+        None => route
+            .template()
+            .split('/')
+            .find(|segment| !segment.is_empty())
+            .unwrap_or("default"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(config: CircuitBreakerConfig) -> (CircuitBreaker, Arc<Mutex<Vec<CircuitEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let breaker = CircuitBreaker::new(config, move |event| recorded.lock().unwrap().push(event.clone()));
+        (breaker, events)
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures() {
+        let (breaker, events) = breaker(CircuitBreakerConfig::new(2, Duration::from_secs(60)));
+
+        breaker.acquire("VpsApiService").unwrap().record(false);
+        breaker.acquire("VpsApiService").unwrap().record(true);
+        breaker.acquire("VpsApiService").unwrap().record(false);
+        assert_eq!(breaker.state("VpsApiService"), CircuitState::Closed);
+
+        breaker.acquire("VpsApiService").unwrap().record(false);
+        assert_eq!(breaker.state("VpsApiService"), CircuitState::Open);
+        match breaker.acquire("VpsApiService") {
+            Err(NodestyError::CircuitOpen { group, retry_after }) => {
+                assert_eq!(group, "VpsApiService");
+                assert!(retry_after.is_some_and(|retry_after| retry_after <= Duration::from_secs(60)));
+            }
+            _ => panic!("expected the circuit to be open"),
+        }
+        assert!(breaker.acquire("FirewallApiService").is_ok());
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].from, events[0].to), (CircuitState::Closed, CircuitState::Open));
+    }
+
+    #[test]
+    fn half_open_trial_closes_or_reopens_the_circuit() {
+        let (breaker, events) = breaker(CircuitBreakerConfig::new(1, Duration::ZERO));
+
+        breaker.acquire("VpsApiService").unwrap().record(false);
+        let trial = breaker.acquire("VpsApiService").unwrap();
+        assert_eq!(breaker.state("VpsApiService"), CircuitState::HalfOpen);
+        assert!(matches!(
+            breaker.acquire("VpsApiService"),
+            Err(NodestyError::CircuitOpen { retry_after: None, .. })
+        ));
+        trial.record(false);
+        assert_eq!(breaker.state("VpsApiService"), CircuitState::Open);
+
+        breaker.acquire("VpsApiService").unwrap().record(true);
+        assert_eq!(breaker.state("VpsApiService"), CircuitState::Closed);
+
+        let transitions: Vec<_> = events.lock().unwrap().iter().map(|event| event.to).collect();
+        assert_eq!(
+            transitions,
+            [
+                CircuitState::Open,
+                CircuitState::HalfOpen,
+                CircuitState::Open,
+                CircuitState::HalfOpen,
+                CircuitState::Closed,
+            ]
+        );
+    }
+
+    #[test]
+    fn outcomes_only_count_under_the_state_they_were_let_through() {
+        let (closed, _) = breaker(CircuitBreakerConfig::new(1, Duration::from_secs(60)));
+
+        // A slow call let through while closed finishes after the circuit opened.
+        let slow = closed.acquire("VpsApiService").unwrap();
+        closed.acquire("VpsApiService").unwrap().record(false);
+        assert_eq!(closed.state("VpsApiService"), CircuitState::Open);
+        slow.record(true);
+        assert_eq!(closed.state("VpsApiService"), CircuitState::Open);
+
+        // Of two half-open trials, the first failure reopens the circuit and the late
+        // success of the other one does not close it.
+        let (half_open, _) = breaker(
+            CircuitBreakerConfig::new(1, Duration::ZERO).with_half_open_max_calls(2),
+        );
+        half_open.acquire("VpsApiService").unwrap().record(false);
+        let first = half_open.acquire("VpsApiService").unwrap();
+        let second = half_open.acquire("VpsApiService").unwrap();
+        first.record(false);
+        assert_eq!(half_open.state("VpsApiService"), CircuitState::Open);
+        second.record(true);
+        assert_eq!(half_open.state("VpsApiService"), CircuitState::Open);
+    }
+
+    #[test]
+    fn dropped_trial_gives_its_slot_back() {
+        let (breaker, _) = breaker(CircuitBreakerConfig::new(1, Duration::ZERO));
+
+        breaker.acquire("VpsApiService").unwrap().record(false);
+        drop(breaker.acquire("VpsApiService").unwrap());
+        assert_eq!(breaker.state("VpsApiService"), CircuitState::HalfOpen);
+        assert!(breaker.acquire("VpsApiService").is_ok());
+    }
+
+    #[test]
+    fn groups_follow_the_service_or_first_path_segment() {
+        let route = Route::new("VpsApiService::get_details", "/services/{id}/vps/info");
+        assert_eq!(group(&route), "VpsApiService");
+        assert_eq!(group(&Route::from("/tickets/5")), "tickets");
+        assert_eq!(group(&Route::from("/")), "default");
+    }
+}
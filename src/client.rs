@@ -1,71 +1,557 @@
+mod builder;
+
+pub use builder::NodestyApiClientBuilder;
+
+use crate::audit::{AuditRecord, AuditSink};
+use crate::auth::TokenProvider;
+use crate::cache::{Lookup, ResponseCache};
+use crate::circuit_breaker::{self, CircuitBreaker, CircuitState};
+#[cfg(feature = "metrics")]
+use crate::metrics::ClientMetrics;
+use crate::concurrency::{ConcurrencyLimiter, Priority};
+use crate::dry_run::{self, DryRun, PlannedMutation};
+use crate::lenient;
+use crate::middleware::{Middleware, Next};
 use crate::models::RestClientOptions;
-use crate::ApiResponse;
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
-use reqwest::{Client, ClientBuilder, Error, Method};
+use crate::request::{self, ApiCall, RequestOptions};
+use crate::rate_limit::{self, RateLimiter};
+use crate::retry::{self, RetryPolicy};
+use crate::single_flight::{self, Flight, SingleFlight};
+use crate::telemetry::RequestTelemetry;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+use crate::{ApiResponse, NodestyError, Route};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, IF_NONE_MATCH};
+use reqwest::{Method, StatusCode};
 use serde::de::DeserializeOwned;
 use serde_json;
 use serde_json::Value;
+use std::fmt;
 use std::sync::Arc;
 use std::time::Duration;
 
 pub struct NodestyApiClient {
-    client: Client,
+    transport: Arc<dyn Transport>,
+    middlewares: Vec<Arc<dyn Middleware>>,
     base_url: String,
+    token_provider: Arc<dyn TokenProvider>,
+    timeout: Duration,
+    retry: RetryPolicy,
+    rate_limiter: RateLimiter,
+    lenient_decoding: bool,
+    idempotency_keys: bool,
+    dry_run: Option<DryRun>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    audit_actor: Option<String>,
+    cache: Option<ResponseCache>,
+    in_flight: Option<SingleFlight>,
+    circuit_breaker: Option<CircuitBreaker>,
+    concurrency: Option<ConcurrencyLimiter>,
+    #[cfg(feature = "metrics")]
+    metrics: ClientMetrics,
+}
+
+impl fmt::Debug for NodestyApiClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodestyApiClient")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
 }
 
 impl NodestyApiClient {
-    pub fn new(options: RestClientOptions) -> Result<Arc<Self>, Error> {
-        let auth_header = HeaderValue::from_str(&format!("PAT {}", options.access_token))
-            .expect("Invalid authorization token");
+    pub fn builder() -> NodestyApiClientBuilder {
+        NodestyApiClientBuilder::new()
+    }
 
-        let timeout = Duration::from_millis(options.timeout_ms.unwrap_or(30_000));
+    pub fn new(options: RestClientOptions) -> Result<Self, NodestyError> {
+        Self::builder().options(options).build()
+    }
 
-        let mut headers = HeaderMap::new();
-        headers.insert(AUTHORIZATION, auth_header);
+    /// The Prometheus collectors this client records into.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &ClientMetrics {
+        &self.metrics
+    }
+
+    /// Drops every cached response. A no-op unless caching is enabled.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Drops the cached responses of one service, for example after it was changed elsewhere.
+    pub fn invalidate_cache(&self, service_id: &str) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_service(service_id);
+        }
+    }
+
+    /// The state of the circuit breaker guarding `group`, such as `VpsApiService`. Always
+    /// closed unless the circuit breaker is enabled.
+    pub fn circuit_state(&self, group: &str) -> CircuitState {
+        self.circuit_breaker
+            .as_ref()
+            .map_or(CircuitState::Closed, |breaker| breaker.state(group))
+    }
+
+    /// Requests currently holding a concurrency slot. Always zero unless
+    /// [`max_concurrency`](RestClientOptions::with_max_concurrency) is set.
+    pub fn active_requests(&self) -> usize {
+        self.concurrency.as_ref().map_or(0, ConcurrencyLimiter::active)
+    }
+
+    /// Requests waiting for a concurrency slot.
+    pub fn queued_requests(&self) -> usize {
+        self.concurrency.as_ref().map_or(0, ConcurrencyLimiter::queue_depth)
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// The mutating calls recorded instead of sent so far in dry-run mode, oldest first.
+    pub fn planned_mutations(&self) -> Vec<PlannedMutation> {
+        self.dry_run.as_ref().map(DryRun::planned).unwrap_or_default()
+    }
 
-        let client = ClientBuilder::new()
-            .default_headers(headers)
-            .timeout(timeout)
-            .connect_timeout(timeout)
-            .build()?;
+    /// Like [`planned_mutations`](Self::planned_mutations), but also clears the list.
+    pub fn take_planned_mutations(&self) -> Vec<PlannedMutation> {
+        self.dry_run.as_ref().map(DryRun::take).unwrap_or_default()
+    }
 
-        Ok(Arc::new(Self {
-            client,
-            base_url: options.base_url.clone(),
-        }))
+    /// Prepares a request that can be adjusted with per-call options before it is awaited.
+    /// Transient failures are retried when `method` is idempotent or the request carries an
+    /// `Idempotency-Key`, which POSTs get by default.
+    pub fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        route: impl Into<Route>,
+        body: Option<Value>,
+    ) -> ApiCall<'_, T> {
+        let retry_safe = retry::is_idempotent(&method);
+        ApiCall::new(self, method, route.into(), body, retry_safe)
     }
 
+    /// Sends a request, retrying transient failures when `method` is idempotent or the request
+    /// carries an `Idempotency-Key`.
     pub async fn send_request<T: DeserializeOwned>(
         &self,
         method: Method,
-        path: &str,
+        route: impl Into<Route>,
         body: Option<Value>,
-    ) -> Result<ApiResponse<T>, Error> {
-        let url = format!("{}{}", self.base_url, path);
-        let mut request = self.client.request(method, &url);
+    ) -> Result<ApiResponse<T>, NodestyError> {
+        self.request(method, route, body).await
+    }
 
-        if let Some(body) = body {
-            request = request.json(&body);
+    /// Like [`send_request`](Self::send_request), but retries transient failures regardless
+    /// of `method`. Only use this for calls the API is known to handle idempotently.
+    pub async fn send_retryable_request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        route: impl Into<Route>,
+        body: Option<Value>,
+    ) -> Result<ApiResponse<T>, NodestyError> {
+        self.request(method, route, body).retryable().await
+    }
+
+    pub(crate) async fn execute<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        route: Route,
+        body: Option<Value>,
+        retry_safe: bool,
+        options: RequestOptions,
+    ) -> Result<ApiResponse<T>, NodestyError> {
+        if let Some(dry_run) = self.dry_run.as_ref().filter(|_| !dry_run::is_read_only(&method)) {
+            dry_run.record(&method, &route, body.as_ref());
+            #[cfg(feature = "tracing")]
+            tracing::info!(
+                http.method = %method,
+                path = route.path(),
+                operation = route.operation(),
+                "dry run: recorded nodesty request instead of sending it"
+            );
+            return Ok(ApiResponse {
+                success: true,
+                error: None,
+                data: T::deserialize(Value::Null).ok(),
+                raw: None,
+            });
         }
 
-        let response = request.send().await?;
-        let status = response.status();
-        let raw_data: Value = response.json().await?;
+        let telemetry = RequestTelemetry::start(self, &method, &route);
+        let mut status = None;
+        let priority = options.priority.unwrap_or_else(Priority::current);
+        let audit = self
+            .audit_sink
+            .as_deref()
+            .filter(|_| !dry_run::is_read_only(&method))
+            .map(|sink| PendingAudit {
+                sink,
+                telemetry: &telemetry,
+                method: method.clone(),
+                route: &route,
+                body: body.clone(),
+                actor: options.actor.clone().or_else(|| self.audit_actor.clone()),
+                written: false,
+            });
 
-        let api_response = if !status.is_success() {
-            ApiResponse {
-                success: false,
-                error: raw_data["message"].as_str().map(|s| s.to_string()),
-                data: None,
+        let call = async {
+            let request = self.build_request(method, &route, body, &options)?;
+            // The API applies a mutation at most once per key, so retrying it is safe.
+            let retry_safe = retry_safe || request.headers.contains_key(retry::IDEMPOTENCY_KEY);
+            let response = self.fetch_shared(request, &route, retry_safe, &telemetry).await?;
+            status = Some(response.status.as_u16());
+            self.decode(response)
+        };
+        let result = telemetry
+            .instrument(priority.scope(request::cancellable(call, options.cancellation.as_ref())))
+            .await;
+
+        let status = status.or_else(|| {
+            let error = result.as_ref().err()?;
+            error.status().map(|status| status.as_u16())
+        });
+        telemetry.finish(&result, status);
+
+        if let Some(mut audit) = audit {
+            audit.write(status, result.as_ref().err().map(NodestyError::kind));
+        }
+        result
+    }
+
+    fn build_request(
+        &self,
+        method: Method,
+        route: &Route,
+        body: Option<Value>,
+        options: &RequestOptions,
+    ) -> Result<HttpRequest, NodestyError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+        headers.extend(options.headers.clone());
+
+        let idempotency_key = match &options.idempotency_key {
+            Some(key) => Some(key.clone()),
+            None if self.idempotency_keys && method == Method::POST => Some(retry::idempotency_key()),
+            None => None,
+        };
+        if let Some(key) = idempotency_key {
+            let value = HeaderValue::from_str(&key).map_err(|_| {
+                NodestyError::config("idempotency_key", "must be a valid header value")
+            })?;
+            headers.insert(retry::IDEMPOTENCY_KEY, value);
+        }
+
+        let body = match body {
+            Some(body) => {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                Some(serde_json::to_vec(&body).map_err(|error| NodestyError::Encode {
+                    message: error.to_string(),
+                })?)
             }
-        } else {
-            ApiResponse {
-                success: true,
-                error: None,
-                data: serde_json::from_value(raw_data).ok(),
+            None => None,
+        };
+
+        Ok(HttpRequest {
+            method,
+            url: format!("{}{}", self.base_url, route.path()),
+            headers,
+            body,
+            timeout: Some(options.timeout.unwrap_or(self.timeout)),
+        })
+    }
+
+    /// Coalesces concurrent identical GETs into one request when coalescing is enabled.
+    async fn fetch_shared(
+        &self,
+        request: HttpRequest,
+        route: &Route,
+        retry_safe: bool,
+        telemetry: &RequestTelemetry<'_>,
+    ) -> Result<HttpResponse, NodestyError> {
+        let Some(in_flight) = self.in_flight.as_ref().filter(|_| request.method == Method::GET) else {
+            return self.fetch_cached(request, route, retry_safe, telemetry).await;
+        };
+
+        match in_flight.join(single_flight::key(&request)) {
+            Flight::Leader(guard) => {
+                let result = self.fetch_cached(request, route, retry_safe, telemetry).await;
+                guard.complete(&result);
+                result
             }
+            Flight::Follower(receiver) => match receiver.await {
+                Ok(result) => result,
+                // The leader was cancelled before it got a response.
+                Err(_) => self.fetch_cached(request, route, retry_safe, telemetry).await,
+            },
+        }
+    }
+
+    /// Serves GETs from the response cache when one is configured, revalidates stale entries
+    /// and invalidates the service's entries after any mutating call.
+    async fn fetch_cached(
+        &self,
+        mut request: HttpRequest,
+        route: &Route,
+        retry_safe: bool,
+        telemetry: &RequestTelemetry<'_>,
+    ) -> Result<HttpResponse, NodestyError> {
+        let Some(cache) = &self.cache else {
+            return self.fetch(request, route, retry_safe, telemetry).await;
+        };
+
+        match request.method {
+            Method::GET => {}
+            Method::HEAD | Method::OPTIONS => return self.fetch(request, route, retry_safe, telemetry).await,
+            _ => {
+                let result = self.fetch(request, route, retry_safe, telemetry).await;
+                cache.invalidate(route);
+                return result;
+            }
+        }
+
+        let (lookup, generation) = cache.lookup(route);
+        let stale = match lookup {
+            Lookup::Fresh(response) => return Ok(response),
+            Lookup::Stale { response, etag } => {
+                request.headers.insert(IF_NONE_MATCH, etag);
+                Some(response)
+            }
+            Lookup::Miss => None,
         };
 
-        Ok(api_response)
+        let response = self.fetch(request, route, retry_safe, telemetry).await?;
+        if response.status == StatusCode::NOT_MODIFIED
+            && let Some(stale) = stale
+        {
+            // The entry may have been invalidated or evicted while the request was in flight;
+            // the 304 still vouches for the copy it was revalidated against.
+            return Ok(cache.revalidated(route, generation).unwrap_or(stale));
+        }
+        cache.store(route, &response, generation);
+        Ok(response)
+    }
+
+    /// Runs `request` through the middleware chain and transport, retrying transient failures.
+    /// A 401 makes the token provider refresh the token and the request is replayed once.
+    /// Each attempt holds a concurrency slot, queued at the [`Priority`] of the enclosing scope.
+    /// While the circuit of the route's endpoint group is open, attempts fail fast.
+    async fn fetch(
+        &self,
+        request: HttpRequest,
+        route: &Route,
+        retry_safe: bool,
+        telemetry: &RequestTelemetry<'_>,
+    ) -> Result<HttpResponse, NodestyError> {
+        let mut attempt = 0;
+        let mut refreshed = false;
+
+        loop {
+            let slot = match &self.concurrency {
+                Some(limiter) => {
+                    let priority = Priority::current();
+                    let (slot, waited) = limiter.acquire(priority).await;
+                    telemetry.queued(priority, waited);
+                    Some(slot)
+                }
+                None => None,
+            };
+            let circuit = match &self.circuit_breaker {
+                Some(breaker) => Some(breaker.acquire(circuit_breaker::group(route))?),
+                None => None,
+            };
+            telemetry.rate_limited(self.rate_limiter.acquire().await);
+
+            let token = self.token_provider.token().await?;
+            let mut attempt_request = request.clone();
+            attempt_request.headers.insert(AUTHORIZATION, builder::auth_header(&token)?);
+
+            let result = Next::new(&self.middlewares, self.transport.as_ref())
+                .run(attempt_request)
+                .await;
+            telemetry.attempt(attempt, &result);
+            if let Some(circuit) = circuit {
+                circuit.record(!is_outage(&result));
+            }
+            if let Ok(response) = &result {
+                self.rate_limiter.update(response.status, &response.headers);
+            }
+
+            let unauthorized =
+                matches!(&result, Ok(response) if response.status == StatusCode::UNAUTHORIZED);
+            if unauthorized && !refreshed {
+                refreshed = true;
+                // Replaying only helps if the provider actually has a different token. If it
+                // cannot get one, the caller still gets the 401 rather than the provider's error.
+                match self.token_provider.refresh(&token).await {
+                    Ok(refreshed) if refreshed != token => continue,
+                    Ok(_) => {}
+                    Err(error) => telemetry.refresh_failed(&error),
+                }
+            }
+
+            let retryable = match &result {
+                Ok(response) => retry::is_retryable_status(response.status),
+                Err(error) => retry::is_retryable_error(error),
+            };
+
+            if retryable && retry_safe && self.retry.should_retry(attempt) {
+                // The limiter already holds the next attempt back until the window resets.
+                let delay = if self.rate_limiter.is_limited() {
+                    Duration::ZERO
+                } else {
+                    self.retry.backoff(attempt)
+                };
+                telemetry.retrying(attempt, &result, delay);
+                drop(slot);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+
+            let response = result?;
+            if attempt == 0 && response.status.is_success() {
+                self.retry.record_success();
+            }
+            return Ok(response);
+        }
     }
-}
\ No newline at end of file
+
+    fn decode<T: DeserializeOwned>(&self, response: HttpResponse) -> Result<ApiResponse<T>, NodestyError> {
+        let status = response.status;
+        let retry_after = rate_limit::retry_after(&response.headers);
+        let content_type = response
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let bytes = response.body;
+
+        let raw_data = match parse_body(status, content_type.as_deref(), &bytes) {
+            Some(raw_data) => raw_data,
+            // Actions like `perform_action` may answer with a plain "OK"; that is fine as long
+            // as the caller does not expect a payload.
+            None if status.is_success() => match T::deserialize(Value::Null) {
+                Ok(data) => {
+                    return Ok(ApiResponse {
+                        success: true,
+                        error: None,
+                        data: Some(data),
+                        raw: None,
+                    });
+                }
+                Err(_) => {
+                    return Err(NodestyError::unexpected_content(status, content_type, &bytes));
+                }
+            },
+            None => return Err(NodestyError::unexpected_content(status, content_type, &bytes)),
+        };
+
+        if !status.is_success() {
+            return Err(NodestyError::from_status(status, &raw_data, retry_after));
+        }
+
+        let error = match serde_path_to_error::deserialize(&raw_data) {
+            Ok(data) => {
+                return Ok(ApiResponse {
+                    success: true,
+                    error: None,
+                    data: Some(data),
+                    raw: None,
+                });
+            }
+            Err(error) => error,
+        };
+
+        // Calls without a payload are typed `()`, but the API may still acknowledge them with
+        // a body such as `{"success": true, "message": "Action queued"}`.
+        if let Ok(data) = T::deserialize(Value::Null) {
+            return Ok(ApiResponse {
+                success: true,
+                error: None,
+                data: Some(data),
+                raw: None,
+            });
+        }
+
+        if self.lenient_decoding {
+            let (data, errors) = lenient::decode(&raw_data);
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            return Ok(ApiResponse {
+                success: true,
+                error: Some(errors.join("; ")),
+                data,
+                raw: Some(raw_data),
+            });
+        }
+        Err(NodestyError::decode(error, &raw_data))
+    }
+}
+
+/// The audit record of a mutating call in progress. If the call is dropped before it
+/// completes, the request may already have reached the API, so the record is still written,
+/// without a status and with the error `cancelled`.
+struct PendingAudit<'a> {
+    sink: &'a dyn AuditSink,
+    telemetry: &'a RequestTelemetry<'a>,
+    method: Method,
+    route: &'a Route,
+    body: Option<Value>,
+    actor: Option<String>,
+    written: bool,
+}
+
+impl PendingAudit<'_> {
+    fn write(&mut self, status: Option<u16>, error: Option<&'static str>) {
+        self.written = true;
+        let record = AuditRecord::new(
+            &self.method,
+            self.route,
+            self.body.as_ref(),
+            status,
+            error,
+            self.actor.take(),
+        );
+        if let Err(error) = self.sink.record(&record) {
+            self.telemetry.audit_failed(&error);
+        }
+    }
+}
+
+impl Drop for PendingAudit<'_> {
+    fn drop(&mut self) {
+        if !self.written {
+            self.write(None, Some(NodestyError::Cancelled.kind()));
+        }
+    }
+}
+
+/// Whether an attempt counts against the circuit breaker: the API could not be reached or
+/// failed on its side. Client errors and rate limiting mean it is up and answering.
+fn is_outage(result: &Result<HttpResponse, NodestyError>) -> bool {
+    match result {
+        Ok(response) => response.status.is_server_error(),
+        Err(error) => matches!(error, NodestyError::Transport(_) | NodestyError::Timeout),
+    }
+}
+
+/// Returns `Value::Null` for empty bodies and the parsed JSON for JSON bodies. Anything else
+/// (HTML error pages from the edge proxy, plain text) yields `None`.
+fn parse_body(status: StatusCode, content_type: Option<&str>, bytes: &[u8]) -> Option<Value> {
+    if status == StatusCode::NO_CONTENT || bytes.iter().all(|b| b.is_ascii_whitespace()) {
+        return Some(Value::Null);
+    }
+
+    let is_json = content_type.is_none_or(|content_type| {
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+        mime.eq_ignore_ascii_case("application/json") || mime.to_ascii_lowercase().ends_with("+json")
+    });
+
+    if is_json {
+        serde_json::from_slice(bytes).ok()
+    } else {
+        None
+    }
+}
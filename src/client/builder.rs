@@ -0,0 +1,463 @@
+use super::NodestyApiClient;
+use crate::audit::AuditSink;
+use crate::auth::{StaticTokenProvider, TokenProvider};
+use crate::cache::ResponseCache;
+use crate::circuit_breaker::{CircuitBreaker, CircuitEvent, CircuitListener};
+#[cfg(feature = "metrics")]
+use crate::metrics::ClientMetrics;
+use crate::concurrency::ConcurrencyLimiter;
+use crate::dry_run::DryRun;
+use crate::middleware::Middleware;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use crate::models::ClientIdentity;
+use crate::models::{AccessToken, ProxyConfig, RestClientOptions};
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use crate::single_flight::SingleFlight;
+use crate::telemetry;
+use crate::transport::{ReqwestTransport, Transport};
+use crate::NodestyError;
+use reqwest::header::HeaderValue;
+use reqwest::{ClientBuilder, Url};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builds a [`NodestyApiClient`], validating the configuration up front instead of panicking.
+#[derive(Default)]
+pub struct NodestyApiClientBuilder {
+    options: Option<RestClientOptions>,
+    access_token: Option<AccessToken>,
+    base_url: Option<String>,
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    transport: Option<Arc<dyn Transport>>,
+    middlewares: Vec<Arc<dyn Middleware>>,
+    circuit_listeners: Vec<CircuitListener>,
+    audit_sink: Option<Arc<dyn AuditSink>>,
+    audit_actor: Option<String>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<ClientMetrics>,
+}
+
+impl fmt::Debug for NodestyApiClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodestyApiClientBuilder")
+            .field("options", &self.options)
+            .field("access_token", &self.access_token)
+            .field("base_url", &self.base_url)
+            .field("custom_token_provider", &self.token_provider.is_some())
+            .field("custom_transport", &self.transport.is_some())
+            .field("middlewares", &self.middlewares.len())
+            .field("circuit_listeners", &self.circuit_listeners.len())
+            .field("audit_sink", &self.audit_sink.is_some())
+            .field("audit_actor", &self.audit_actor)
+            .finish_non_exhaustive()
+    }
+}
+
+impl NodestyApiClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from a full set of options. `access_token` and `base_url` set on the
+    /// builder take precedence over the values in `options`.
+    pub fn options(mut self, options: RestClientOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    pub fn access_token(mut self, access_token: impl Into<AccessToken>) -> Self {
+        self.access_token = Some(access_token.into());
+        self
+    }
+
+    /// Asks `token_provider` for the token on every request instead of using a fixed
+    /// access token, which is then no longer required.
+    pub fn token_provider(mut self, token_provider: impl TokenProvider + 'static) -> Self {
+        self.token_provider = Some(Arc::new(token_provider));
+        self
+    }
+
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Sends requests through `transport` instead of the default reqwest-based one.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Shares an existing, already tuned [`reqwest::Client`]. Its own default headers and
+    /// timeouts stay in effect; the token and `timeout_ms` are still applied per request.
+    pub fn http_client(self, client: reqwest::Client) -> Self {
+        self.transport(ReqwestTransport::new(client))
+    }
+
+    /// Appends `middleware` to the chain every request attempt passes through.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Calls `listener` whenever a circuit breaker opens, half-opens or closes, for example
+    /// to page someone. Only has an effect with
+    /// [`RestClientOptions::with_circuit_breaker`].
+    pub fn on_circuit_change(
+        mut self,
+        listener: impl Fn(&CircuitEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.circuit_listeners.push(Arc::new(listener));
+        self
+    }
+
+    /// Hands an [`AuditRecord`](crate::audit::AuditRecord) for every non-GET call to `sink`.
+    pub fn audit_sink(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.audit_sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// The actor recorded for calls that do not name one themselves, for example the name of
+    /// the service or job using this client.
+    pub fn audit_actor(mut self, actor: impl Into<String>) -> Self {
+        self.audit_actor = Some(actor.into());
+        self
+    }
+
+    /// Records into `metrics` instead of a registry of the client's own. Use
+    /// [`ClientMetrics::with_registry`] to mount the collectors into an existing registry.
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: ClientMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn build(self) -> Result<NodestyApiClient, NodestyError> {
+        let mut options = match (self.options, self.access_token) {
+            (Some(mut options), access_token) => {
+                if let Some(access_token) = access_token {
+                    options.access_token = access_token;
+                }
+                options
+            }
+            (None, Some(access_token)) => RestClientOptions::new(access_token),
+            (None, None) if self.token_provider.is_some() => RestClientOptions::new(""),
+            (None, None) => {
+                return Err(NodestyError::config("access_token", "an access token is required"));
+            }
+        };
+        if let Some(base_url) = self.base_url {
+            options.base_url = base_url;
+        }
+
+        let base_url = normalize_base_url(&options.base_url)?;
+
+        let timeout_ms = options.timeout_ms.unwrap_or(30_000);
+        if timeout_ms == 0 {
+            return Err(NodestyError::config("timeout_ms", "must be greater than zero"));
+        }
+        let timeout = Duration::from_millis(timeout_ms);
+
+        let base_delay = Duration::from_millis(options.retry_base_delay_ms.unwrap_or(200));
+        let max_delay = Duration::from_millis(options.retry_max_delay_ms.unwrap_or(10_000));
+        if base_delay > max_delay {
+            return Err(NodestyError::config(
+                "retry_base_delay_ms",
+                "must not be greater than retry_max_delay_ms",
+            ));
+        }
+
+        let token_provider = match self.token_provider {
+            Some(token_provider) => token_provider,
+            None => {
+                auth_header(&options.access_token)?;
+                Arc::new(StaticTokenProvider::new(options.access_token.clone()))
+            }
+        };
+
+        let transport: Arc<dyn Transport> = match self.transport {
+            Some(transport) => {
+                if let Some(field) = network_option(&options) {
+                    return Err(NodestyError::config(
+                        field,
+                        "cannot be combined with a custom transport or http client",
+                    ));
+                }
+                transport
+            }
+            None => Arc::new(ReqwestTransport::new(http_client(&options, timeout)?)),
+        };
+
+        let retry = RetryPolicy::new(
+            options.retry.unwrap_or(0),
+            base_delay,
+            max_delay,
+            options.retry_budget,
+        );
+
+        let rate_limiter =
+            RateLimiter::new(Duration::from_millis(options.rate_limit_offset_ms.unwrap_or(0)));
+
+        #[cfg(feature = "metrics")]
+        let metrics = match self.metrics {
+            Some(metrics) => metrics,
+            None => ClientMetrics::new()
+                .map_err(|error| NodestyError::config("metrics", error.to_string()))?,
+        };
+
+        let circuit_breaker = match options.circuit_breaker {
+            Some(config) => {
+                if config.failure_threshold == 0 {
+                    return Err(NodestyError::config(
+                        "circuit_breaker.failure_threshold",
+                        "must be greater than zero",
+                    ));
+                }
+                if config.half_open_max_calls == 0 {
+                    return Err(NodestyError::config(
+                        "circuit_breaker.half_open_max_calls",
+                        "must be greater than zero",
+                    ));
+                }
+                let listeners = self.circuit_listeners;
+                #[cfg(feature = "metrics")]
+                let metrics = metrics.clone();
+                Some(CircuitBreaker::new(config, move |event| {
+                    telemetry::circuit_changed(event);
+                    #[cfg(feature = "metrics")]
+                    metrics.observe_circuit_change(event);
+                    for listener in &listeners {
+                        listener(event);
+                    }
+                }))
+            }
+            None => None,
+        };
+
+        let concurrency = match options.max_concurrency {
+            Some(0) => {
+                return Err(NodestyError::config("max_concurrency", "must be greater than zero"));
+            }
+            Some(max) => {
+                let limiter = ConcurrencyLimiter::new(max);
+                #[cfg(feature = "metrics")]
+                let limiter = limiter.with_metrics(metrics.clone());
+                Some(limiter)
+            }
+            None => None,
+        };
+
+        Ok(NodestyApiClient {
+            transport,
+            middlewares: self.middlewares,
+            base_url,
+            token_provider,
+            timeout,
+            retry,
+            rate_limiter,
+            lenient_decoding: options.lenient_decoding,
+            idempotency_keys: options.idempotency_keys,
+            dry_run: options.dry_run.then(DryRun::default),
+            audit_sink: self.audit_sink,
+            audit_actor: self.audit_actor,
+            cache: options.cache.map(ResponseCache::new),
+            in_flight: options.coalesce_requests.then(SingleFlight::default),
+            circuit_breaker,
+            concurrency,
+            #[cfg(feature = "metrics")]
+            metrics,
+        })
+    }
+}
+
+/// Builds the default reqwest client, applying the proxy and TLS options.
+fn http_client(options: &RestClientOptions, timeout: Duration) -> Result<reqwest::Client, NodestyError> {
+    let mut builder = ClientBuilder::new().connect_timeout(timeout);
+
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(reqwest_proxy(proxy)?);
+    }
+    builder = apply_tls(builder, options)?;
+
+    builder
+        .build()
+        .map_err(|error| NodestyError::config("http_client", error.to_string()))
+}
+
+/// The first option that only the default reqwest client can honour.
+fn network_option(options: &RestClientOptions) -> Option<&'static str> {
+    if options.proxy.is_some() {
+        Some("proxy")
+    } else if !options.root_certificates.is_empty() {
+        Some("root_certificates")
+    } else if options.client_identity.is_some() {
+        Some("client_identity")
+    } else if !options.pinned_spki_sha256.is_empty() {
+        Some("pinned_spki_sha256")
+    } else {
+        None
+    }
+}
+
+fn reqwest_proxy(proxy: &ProxyConfig) -> Result<reqwest::Proxy, NodestyError> {
+    // The URL may carry credentials, so it is left out of the error messages.
+    let url = Url::parse(proxy.url.trim())
+        .map_err(|error| NodestyError::config("proxy", format!("invalid URL: {}", error)))?;
+
+    match url.scheme() {
+        "http" | "https" => {}
+        "socks5" | "socks5h" if cfg!(feature = "socks") => {}
+        "socks5" | "socks5h" => {
+            return Err(NodestyError::config(
+                "proxy",
+                "SOCKS proxies require the `socks` feature",
+            ));
+        }
+        scheme => {
+            return Err(NodestyError::config(
+                "proxy",
+                format!(
+                    "unsupported scheme `{}`, expected http, https or socks5",
+                    scheme
+                ),
+            ));
+        }
+    }
+
+    let mut reqwest_proxy = reqwest::Proxy::all(url)
+        .map_err(|error| NodestyError::config("proxy", error.to_string()))?;
+    if let Some((username, password)) = &proxy.basic_auth {
+        reqwest_proxy = reqwest_proxy.basic_auth(username, password.expose());
+    }
+    if let Some(no_proxy) = &proxy.no_proxy {
+        reqwest_proxy = reqwest_proxy.no_proxy(reqwest::NoProxy::from_string(no_proxy));
+    }
+    Ok(reqwest_proxy)
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn apply_tls(
+    mut builder: ClientBuilder,
+    options: &RestClientOptions,
+) -> Result<ClientBuilder, NodestyError> {
+    if !options.pinned_spki_sha256.is_empty() {
+        #[cfg(feature = "rustls")]
+        return Ok(builder.use_preconfigured_tls(crate::tls::pinned_config(
+            &options.root_certificates,
+            options.client_identity.as_ref(),
+            &options.pinned_spki_sha256,
+        )?));
+        #[cfg(not(feature = "rustls"))]
+        return Err(NodestyError::config(
+            "pinned_spki_sha256",
+            "certificate pinning requires the `rustls` feature",
+        ));
+    }
+
+    for pem in &options.root_certificates {
+        let certificates = reqwest::Certificate::from_pem_bundle(pem)
+            .map_err(|error| NodestyError::config("root_certificates", error.to_string()))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if let Some(identity) = &options.client_identity {
+        builder = builder.identity(reqwest_identity(identity)?);
+    }
+    Ok(builder)
+}
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+fn apply_tls(
+    builder: ClientBuilder,
+    options: &RestClientOptions,
+) -> Result<ClientBuilder, NodestyError> {
+    match network_option(options) {
+        Some("proxy") | None => Ok(builder),
+        Some(field) => Err(NodestyError::config(
+            field,
+            "requires the `native-tls` or `rustls` feature",
+        )),
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn reqwest_identity(identity: &ClientIdentity) -> Result<reqwest::Identity, NodestyError> {
+    let identity = match identity {
+        ClientIdentity::Pem {
+            certificate_chain,
+            private_key,
+        } => {
+            #[cfg(feature = "native-tls")]
+            let identity = reqwest::Identity::from_pkcs8_pem(certificate_chain, private_key);
+            #[cfg(not(feature = "native-tls"))]
+            let identity = reqwest::Identity::from_pem(
+                &[certificate_chain.as_slice(), b"\n", private_key.as_slice()].concat(),
+            );
+            identity
+        }
+        #[cfg(feature = "native-tls")]
+        ClientIdentity::Pkcs12 { der, password } => {
+            reqwest::Identity::from_pkcs12_der(der, password.expose())
+        }
+        #[cfg(not(feature = "native-tls"))]
+        ClientIdentity::Pkcs12 { .. } => {
+            return Err(NodestyError::config(
+                "client_identity",
+                "PKCS#12 identities require the `native-tls` feature",
+            ));
+        }
+    };
+    identity.map_err(|error| NodestyError::config("client_identity", error.to_string()))
+}
+
+pub(super) fn auth_header(access_token: &AccessToken) -> Result<HeaderValue, NodestyError> {
+    let token = access_token.expose().trim();
+    if token.is_empty() {
+        return Err(NodestyError::config("access_token", "must not be empty"));
+    }
+
+    let mut header = HeaderValue::from_str(&format!("PAT {}", token)).map_err(|_| {
+        NodestyError::config(
+            "access_token",
+            "contains characters that are not allowed in an HTTP header",
+        )
+    })?;
+    header.set_sensitive(true);
+    Ok(header)
+}
+
+/// Accepts `nodesty.com/api`, `https://nodesty.com/api/` and the like, and returns the URL
+/// with a scheme and without a trailing slash so it can be joined with `/services/...` paths.
+fn normalize_base_url(base_url: &str) -> Result<String, NodestyError> {
+    let trimmed = base_url.trim();
+    if trimmed.is_empty() {
+        return Err(NodestyError::config("base_url", "must not be empty"));
+    }
+
+    let with_scheme = if trimmed.contains("://") {
+        trimmed.to_string()
+    } else {
+        format!("https://{}", trimmed)
+    };
+
+    let url = Url::parse(&with_scheme)
+        .map_err(|error| NodestyError::config("base_url", format!("{}: {}", with_scheme, error)))?;
+
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(NodestyError::config(
+            "base_url",
+            format!("unsupported scheme `{}`, expected http or https", url.scheme()),
+        ));
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err(NodestyError::config("base_url", "is missing a host"));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(NodestyError::config("base_url", "must not contain a query or fragment"));
+    }
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
@@ -0,0 +1,321 @@
+#[cfg(feature = "metrics")]
+use crate::metrics::ClientMetrics;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// How urgently a call should get one of the client's concurrency slots, see
+/// [`RestClientOptions::with_max_concurrency`](crate::RestClientOptions::with_max_concurrency).
+/// Calls queue by priority, then in arrival order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Background work such as inventory sweeps.
+    Low,
+    #[default]
+    Normal,
+    /// Interactive calls, for example a user clicking "restart".
+    High,
+}
+
+thread_local! {
+    static CURRENT: Cell<Option<Priority>> = const { Cell::new(None) };
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+
+    /// Runs `future` with this priority: every API call made inside it queues at this priority.
+    ///
+    /// ```ignore
+    /// Priority::High.scope(vps.restart(id)).await?;
+    /// ```
+    pub fn scope<F: IntoFuture>(self, future: F) -> WithPriority<F::IntoFuture> {
+        WithPriority {
+            priority: self,
+            future: Box::pin(future.into_future()),
+        }
+    }
+
+    /// The priority of the innermost enclosing [`scope`](Self::scope).
+    pub(crate) fn current() -> Priority {
+        CURRENT.with(Cell::get).unwrap_or_default()
+    }
+}
+
+/// A future running under a [`Priority`], created by [`Priority::scope`].
+pub struct WithPriority<F> {
+    priority: Priority,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for WithPriority<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let previous = CURRENT.with(|current| current.replace(Some(self.priority)));
+        let result = self.future.as_mut().poll(cx);
+        CURRENT.with(|current| current.set(previous));
+        result
+    }
+}
+
+/// Caps how many attempts one client has in flight. Callers beyond the cap wait in a
+/// priority queue.
+#[derive(Debug)]
+pub(crate) struct ConcurrencyLimiter {
+    max: usize,
+    state: Mutex<LimiterState>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<ClientMetrics>,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    active: usize,
+    queue: BinaryHeap<Waiter>,
+    next_seq: u64,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    priority: Priority,
+    seq: u64,
+    sender: oneshot::Sender<()>,
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    // The heap pops the greatest element: highest priority first, then the earliest arrival.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl ConcurrencyLimiter {
+    pub(crate) fn new(max: usize) -> Self {
+        Self {
+            max,
+            state: Mutex::new(LimiterState::default()),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Mirrors the number of active and queued attempts into `metrics`.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn with_metrics(mut self, metrics: ClientMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+    fn observe(&self, state: &LimiterState) {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_concurrency(state.active, state.queue.len());
+        }
+    }
+
+    pub(crate) fn active(&self) -> usize {
+        self.state.lock().unwrap().active
+    }
+
+    pub(crate) fn queue_depth(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    /// Waits for a free slot. Returns the slot and how long the caller was queued.
+    pub(crate) async fn acquire(&self, priority: Priority) -> (ConcurrencyPermit<'_>, Duration) {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.active < self.max && state.queue.is_empty() {
+                state.active += 1;
+                self.observe(&state);
+                return (ConcurrencyPermit { limiter: self }, Duration::ZERO);
+            }
+            let (sender, receiver) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.queue.push(Waiter {
+                priority,
+                seq,
+                sender,
+            });
+            self.observe(&state);
+            receiver
+        };
+
+        let started = Instant::now();
+        let mut queued = Queued {
+            limiter: self,
+            receiver,
+            granted: false,
+        };
+        // The sender is only dropped by `release`, after a successful hand-over, or never.
+        let _ = (&mut queued.receiver).await;
+        queued.granted = true;
+        (ConcurrencyPermit { limiter: self }, started.elapsed())
+    }
+
+    /// Hands the slot to the next waiter still waiting, or frees it.
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        let mut handed_over = false;
+        while let Some(waiter) = state.queue.pop() {
+            if waiter.sender.send(()).is_ok() {
+                handed_over = true;
+                break;
+            }
+        }
+        if !handed_over {
+            state.active -= 1;
+        }
+        self.observe(&state);
+    }
+}
+
+/// A queued caller. If it is cancelled after a slot was handed to it, the slot is passed on.
+struct Queued<'a> {
+    limiter: &'a ConcurrencyLimiter,
+    receiver: oneshot::Receiver<()>,
+    granted: bool,
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+        // Hand-overs happen under the lock, so after closing the receiver here no slot can
+        // arrive unnoticed.
+        let handed_over = {
+            let mut state = self.limiter.state.lock().unwrap();
+            self.receiver.close();
+            state.queue.retain(|waiter| !waiter.sender.is_closed());
+            self.limiter.observe(&state);
+            self.receiver.try_recv().is_ok()
+        };
+        if handed_over {
+            self.limiter.release();
+        }
+    }
+}
+
+pub(crate) struct ConcurrencyPermit<'a> {
+    limiter: &'a ConcurrencyLimiter,
+}
+
+impl Drop for ConcurrencyPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::poll_fn;
+    use std::pin::pin;
+
+    /// Polls `future` once, registering it with the limiter if it has to wait.
+    async fn poll_once<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+        let mut future = Some(future);
+        poll_fn(|cx| Poll::Ready(future.take().unwrap().poll(cx))).await
+    }
+
+    #[tokio::test]
+    async fn callers_beyond_the_limit_wait_for_a_slot() {
+        let limiter = ConcurrencyLimiter::new(2);
+        let (first, waited) = limiter.acquire(Priority::Normal).await;
+        assert_eq!(waited, Duration::ZERO);
+        let (_second, _) = limiter.acquire(Priority::Normal).await;
+        assert_eq!(limiter.active(), 2);
+
+        let mut third = pin!(limiter.acquire(Priority::Normal));
+        assert!(poll_once(third.as_mut()).await.is_pending());
+        assert_eq!(limiter.queue_depth(), 1);
+
+        drop(first);
+        let third = poll_once(third.as_mut()).await;
+        assert!(third.is_ready());
+        assert_eq!(limiter.active(), 2);
+        assert_eq!(limiter.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn higher_priority_is_served_first() {
+        let limiter = ConcurrencyLimiter::new(1);
+        let (held, _) = limiter.acquire(Priority::Normal).await;
+
+        let mut low = pin!(limiter.acquire(Priority::Low));
+        let mut high = pin!(limiter.acquire(Priority::High));
+        assert!(poll_once(low.as_mut()).await.is_pending());
+        assert!(poll_once(high.as_mut()).await.is_pending());
+
+        drop(held);
+        assert!(poll_once(low.as_mut()).await.is_pending());
+        let Poll::Ready((high_permit, _)) = poll_once(high.as_mut()).await else {
+            panic!("the high priority caller gets the slot");
+        };
+
+        drop(high_permit);
+        assert!(poll_once(low.as_mut()).await.is_ready());
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_passes_its_slot_on() {
+        let limiter = ConcurrencyLimiter::new(1);
+        let (held, _) = limiter.acquire(Priority::Normal).await;
+
+        let mut cancelled = Box::pin(limiter.acquire(Priority::High));
+        let mut next = pin!(limiter.acquire(Priority::Normal));
+        assert!(poll_once(cancelled.as_mut()).await.is_pending());
+        assert!(poll_once(next.as_mut()).await.is_pending());
+
+        // The slot is handed to the cancelled waiter before it notices.
+        drop(held);
+        drop(cancelled);
+        let next = poll_once(next.as_mut()).await;
+        assert!(next.is_ready());
+        assert_eq!(limiter.active(), 1);
+        assert_eq!(limiter.queue_depth(), 0);
+    }
+
+    #[test]
+    fn scope_sets_the_current_priority() {
+        assert_eq!(Priority::current(), Priority::Normal);
+
+        let mut scoped = pin!(Priority::High.scope(async { Priority::current() }));
+        let waker = std::task::Waker::noop();
+        let result = scoped.as_mut().poll(&mut Context::from_waker(waker));
+        assert_eq!(result, Poll::Ready(Priority::High));
+        assert_eq!(Priority::current(), Priority::Normal);
+    }
+}
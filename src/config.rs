@@ -0,0 +1,201 @@
+use crate::models::{ProxyConfig, RestClientOptions};
+use crate::NodestyError;
+#[cfg(feature = "profile")]
+use serde::Deserialize;
+use std::env::{self, VarError};
+#[cfg(feature = "profile")]
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const TOKEN_VAR: &str = "NODESTY_API_TOKEN";
+const BASE_URL_VAR: &str = "NODESTY_BASE_URL";
+const TIMEOUT_VAR: &str = "NODESTY_TIMEOUT_MS";
+const RETRY_VAR: &str = "NODESTY_RETRY";
+const PROXY_VAR: &str = "NODESTY_PROXY";
+const DRY_RUN_VAR: &str = "NODESTY_DRY_RUN";
+#[cfg(feature = "profile")]
+const CONFIG_VAR: &str = "NODESTY_CONFIG";
+#[cfg(feature = "profile")]
+const PROFILE_VAR: &str = "NODESTY_PROFILE";
+
+impl RestClientOptions {
+    /// Reads the options from `NODESTY_API_TOKEN` (required), `NODESTY_BASE_URL`,
+    /// `NODESTY_TIMEOUT_MS`, `NODESTY_RETRY`, `NODESTY_PROXY` and `NODESTY_DRY_RUN`
+    /// (`true` or `false`).
+    pub fn from_env() -> Result<Self, NodestyError> {
+        let access_token = env_var(TOKEN_VAR)?
+            .ok_or_else(|| NodestyError::config(TOKEN_VAR, "is not set"))?;
+        RestClientOptions::new(access_token).with_env_overrides()
+    }
+
+    /// Loads `profile` from the config file named by `NODESTY_CONFIG`, or from
+    /// `$XDG_CONFIG_HOME/nodesty/config.toml` (`~/.config/nodesty/config.toml`).
+    /// `None` selects `NODESTY_PROFILE`, falling back to `default`. Environment
+    /// variables read by [`from_env`](Self::from_env) override the file's values.
+    #[cfg(feature = "profile")]
+    pub fn from_profile(profile: Option<&str>) -> Result<Self, NodestyError> {
+        let path = match env_var(CONFIG_VAR)? {
+            Some(path) => PathBuf::from(path),
+            None => default_config_path()?,
+        };
+        Self::from_profile_file(path, profile)
+    }
+
+    /// Like [`from_profile`](Self::from_profile), reading the given file.
+    #[cfg(feature = "profile")]
+    pub fn from_profile_file(
+        path: impl AsRef<Path>,
+        profile: Option<&str>,
+    ) -> Result<Self, NodestyError> {
+        let path = path.as_ref();
+        let name = match profile {
+            Some(profile) => profile.to_string(),
+            None => env_var(PROFILE_VAR)?.unwrap_or_else(|| "default".to_string()),
+        };
+
+        let text = std::fs::read_to_string(path).map_err(|error| {
+            NodestyError::config(path.display().to_string(), error.to_string())
+        })?;
+        let mut profiles: toml::Table = text.parse().map_err(|error: toml::de::Error| {
+            NodestyError::config(path.display().to_string(), error.to_string())
+        })?;
+        let profile = profiles.remove(&name).ok_or_else(|| {
+            NodestyError::config(name.clone(), format!("profile not found in {}", path.display()))
+        })?;
+        let profile: Profile = serde_path_to_error::deserialize(profile).map_err(|error| {
+            let field = format!("{}.{}", name, error.path());
+            let message = error.into_inner().message().to_string();
+            NodestyError::config(field, format!("{} (in {})", message, path.display()))
+        })?;
+
+        let access_token = match (env_var(TOKEN_VAR)?, profile.token) {
+            (Some(token), _) | (None, Some(token)) => token,
+            (None, None) => {
+                return Err(NodestyError::config(
+                    format!("{}.token", name),
+                    format!("is not set in {} and {} is empty", path.display(), TOKEN_VAR),
+                ));
+            }
+        };
+
+        let mut options = RestClientOptions::new(access_token);
+        if let Some(base_url) = profile.base_url {
+            options.base_url = base_url;
+        }
+        if let Some(timeout_ms) = profile.timeout_ms {
+            options.timeout_ms = Some(timeout_ms);
+        }
+        if let Some(retry) = profile.retry {
+            options.retry = Some(retry);
+        }
+        options.proxy = profile.proxy.map(ProfileProxy::into_config);
+
+        options.with_env_overrides()
+    }
+
+    fn with_env_overrides(mut self) -> Result<Self, NodestyError> {
+        if let Some(base_url) = env_var(BASE_URL_VAR)? {
+            self.base_url = base_url;
+        }
+        if let Some(timeout_ms) = env_parse(TIMEOUT_VAR)? {
+            self.timeout_ms = Some(timeout_ms);
+        }
+        if let Some(retry) = env_parse(RETRY_VAR)? {
+            self.retry = Some(retry);
+        }
+        if let Some(proxy) = env_var(PROXY_VAR)? {
+            self.proxy = Some(ProxyConfig::new(proxy));
+        }
+        if let Some(dry_run) = env_parse(DRY_RUN_VAR)? {
+            self.dry_run = dry_run;
+        }
+        Ok(self)
+    }
+}
+
+/// An unset or empty variable reads as `None`.
+pub(crate) fn env_var(name: &str) -> Result<Option<String>, NodestyError> {
+    match env::var(name) {
+        Ok(value) if value.trim().is_empty() => Ok(None),
+        Ok(value) => Ok(Some(value)),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err(NodestyError::config(name, "is not valid UTF-8")),
+    }
+}
+
+fn env_parse<T: FromStr>(name: &str) -> Result<Option<T>, NodestyError>
+where
+    T::Err: std::fmt::Display,
+{
+    env_var(name)?
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|error| NodestyError::config(name, format!("`{}`: {}", value, error)))
+        })
+        .transpose()
+}
+
+#[cfg(feature = "profile")]
+fn default_config_path() -> Result<PathBuf, NodestyError> {
+    let base = match (env_var("XDG_CONFIG_HOME")?, env_var("HOME")?) {
+        (Some(config_home), _) => PathBuf::from(config_home),
+        (None, Some(home)) => Path::new(&home).join(".config"),
+        (None, None) => {
+            return Err(NodestyError::config(
+                CONFIG_VAR,
+                "is not set and the home directory is unknown",
+            ));
+        }
+    };
+    Ok(base.join("nodesty").join("config.toml"))
+}
+
+/// One `[name]` table of the config file.
+#[cfg(feature = "profile")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    token: Option<String>,
+    base_url: Option<String>,
+    timeout_ms: Option<u64>,
+    retry: Option<u32>,
+    proxy: Option<ProfileProxy>,
+}
+
+/// `proxy = "http://..."` or `[name.proxy]` with `url`, `username`, `password` and `no_proxy`.
+#[cfg(feature = "profile")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ProfileProxy {
+    Url(String),
+    Table(ProfileProxyTable),
+}
+
+#[cfg(feature = "profile")]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileProxyTable {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    no_proxy: Option<String>,
+}
+
+#[cfg(feature = "profile")]
+impl ProfileProxy {
+    fn into_config(self) -> ProxyConfig {
+        match self {
+            ProfileProxy::Url(url) => ProxyConfig::new(url),
+            ProfileProxy::Table(table) => {
+                let mut proxy = ProxyConfig::new(table.url);
+                if let Some(username) = table.username {
+                    proxy = proxy.with_basic_auth(username, table.password.unwrap_or_default());
+                }
+                proxy.no_proxy = table.no_proxy;
+                proxy
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::Route;
+use reqwest::Method;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::sync::Mutex;
+
+/// A mutating call the client did not send because it runs in dry-run mode, see
+/// [`RestClientOptions::with_dry_run`](crate::RestClientOptions::with_dry_run).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedMutation {
+    #[serde(serialize_with = "serialize_method")]
+    pub method: Method,
+    /// The service method that would have sent it, for example `VpsApiService::reinstall`.
+    pub operation: Option<&'static str>,
+    pub template: String,
+    pub path: String,
+    pub service_id: Option<String>,
+    pub body: Option<Value>,
+}
+
+fn serialize_method<S: Serializer>(method: &Method, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(method.as_str())
+}
+
+/// Whether a call only reads and is therefore still sent in dry-run mode.
+pub(crate) fn is_read_only(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DryRun {
+    planned: Mutex<Vec<PlannedMutation>>,
+}
+
+impl DryRun {
+    pub(crate) fn record(&self, method: &Method, route: &Route, body: Option<&Value>) {
+        self.planned.lock().unwrap().push(PlannedMutation {
+            method: method.clone(),
+            operation: route.operation(),
+            template: route.template().to_string(),
+            path: route.path().to_string(),
+            service_id: route.service_id().map(str::to_string),
+            body: body.cloned(),
+        });
+    }
+
+    pub(crate) fn planned(&self) -> Vec<PlannedMutation> {
+        self.planned.lock().unwrap().clone()
+    }
+
+    pub(crate) fn take(&self) -> Vec<PlannedMutation> {
+        std::mem::take(&mut *self.planned.lock().unwrap())
+    }
+}
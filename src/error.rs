@@ -0,0 +1,360 @@
+use crate::transport::TransportError;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum NodestyError {
+    /// The client configuration is invalid. `field` names the offending option.
+    Config { field: String, message: String },
+    /// The request could not be sent or the response could not be read.
+    Transport(TransportError),
+    /// The request did not complete within the configured timeout.
+    Timeout,
+    /// The request body could not be serialized.
+    Encode { message: String },
+    /// 401: the access token is missing, invalid or expired.
+    Unauthorized { message: Option<String> },
+    /// 403: the token is valid but not allowed to access the resource.
+    Forbidden { message: Option<String> },
+    /// 404: the service, ticket, invoice or rule does not exist.
+    NotFound { message: Option<String> },
+    /// 429: too many requests. `retry_after` is taken from the `Retry-After` header.
+    RateLimited { retry_after: Option<Duration> },
+    /// 400/422: the request body was rejected. `fields` maps field names to their messages.
+    Validation {
+        status: StatusCode,
+        message: Option<String>,
+        fields: HashMap<String, Vec<String>>,
+    },
+    /// 5xx: the API failed to handle the request.
+    Server {
+        status: StatusCode,
+        message: Option<String>,
+    },
+    /// Any other unsuccessful response. `status` is `None` for an unsuccessful
+    /// [`ApiResponse`](crate::ApiResponse) envelope that carries no HTTP status.
+    Api {
+        status: Option<StatusCode>,
+        message: Option<String>,
+    },
+    /// The response body did not match the expected model. `path` points at the
+    /// offending field (for example `cpu.cores`) and `body` holds the raw response.
+    Decode {
+        path: Option<String>,
+        message: String,
+        body: Option<String>,
+    },
+    /// The response body was neither empty nor JSON, for example an HTML error page
+    /// served by a proxy. `snippet` holds the beginning of the body.
+    UnexpectedContent {
+        status: StatusCode,
+        content_type: Option<String>,
+        snippet: String,
+    },
+    /// The circuit breaker for `group` is open and the call was not sent. `retry_after` is the
+    /// time left until it lets a trial call through, `None` while trial calls are in flight.
+    CircuitOpen {
+        group: String,
+        retry_after: Option<Duration>,
+    },
+    /// The call was cancelled through its [`CancellationToken`](crate::CancellationToken).
+    Cancelled,
+}
+
+impl NodestyError {
+    pub(crate) fn config(field: impl Into<String>, message: impl Into<String>) -> Self {
+        NodestyError::Config {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn from_status(
+        status: StatusCode,
+        body: &Value,
+        retry_after: Option<Duration>,
+    ) -> Self {
+        let message = body["message"].as_str().map(|s| s.to_string());
+
+        match status {
+            StatusCode::UNAUTHORIZED => NodestyError::Unauthorized { message },
+            StatusCode::FORBIDDEN => NodestyError::Forbidden { message },
+            StatusCode::NOT_FOUND => NodestyError::NotFound { message },
+            StatusCode::TOO_MANY_REQUESTS => NodestyError::RateLimited { retry_after },
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => NodestyError::Validation {
+                status,
+                message,
+                fields: validation_fields(body),
+            },
+            status if status.is_server_error() => NodestyError::Server { status, message },
+            status => NodestyError::Api {
+                status: Some(status),
+                message,
+            },
+        }
+    }
+
+    pub(crate) fn decode(error: serde_path_to_error::Error<serde_json::Error>, body: &Value) -> Self {
+        let path = error.path().to_string();
+        NodestyError::Decode {
+            path: Some(path),
+            message: error.into_inner().to_string(),
+            body: Some(body.to_string()),
+        }
+    }
+
+    pub(crate) fn unexpected_content(
+        status: StatusCode,
+        content_type: Option<String>,
+        body: &[u8],
+    ) -> Self {
+        const SNIPPET_LEN: usize = 512;
+
+        let text = String::from_utf8_lossy(body);
+        let text = text.trim();
+        let end = text
+            .char_indices()
+            .nth(SNIPPET_LEN)
+            .map_or(text.len(), |(index, _)| index);
+
+        NodestyError::UnexpectedContent {
+            status,
+            content_type,
+            snippet: text[..end].to_string(),
+        }
+    }
+
+    /// A copy of the error for every caller that shared one coalesced request.
+    pub(crate) fn duplicate(&self) -> Self {
+        match self {
+            NodestyError::Config { field, message } => NodestyError::Config {
+                field: field.clone(),
+                message: message.clone(),
+            },
+            NodestyError::Transport(error) => NodestyError::Transport(error.duplicate()),
+            NodestyError::Timeout => NodestyError::Timeout,
+            NodestyError::Encode { message } => NodestyError::Encode {
+                message: message.clone(),
+            },
+            NodestyError::Unauthorized { message } => NodestyError::Unauthorized {
+                message: message.clone(),
+            },
+            NodestyError::Forbidden { message } => NodestyError::Forbidden {
+                message: message.clone(),
+            },
+            NodestyError::NotFound { message } => NodestyError::NotFound {
+                message: message.clone(),
+            },
+            NodestyError::RateLimited { retry_after } => NodestyError::RateLimited {
+                retry_after: *retry_after,
+            },
+            NodestyError::Validation {
+                status,
+                message,
+                fields,
+            } => NodestyError::Validation {
+                status: *status,
+                message: message.clone(),
+                fields: fields.clone(),
+            },
+            NodestyError::Server { status, message } => NodestyError::Server {
+                status: *status,
+                message: message.clone(),
+            },
+            NodestyError::Api { status, message } => NodestyError::Api {
+                status: *status,
+                message: message.clone(),
+            },
+            NodestyError::Decode {
+                path,
+                message,
+                body,
+            } => NodestyError::Decode {
+                path: path.clone(),
+                message: message.clone(),
+                body: body.clone(),
+            },
+            NodestyError::UnexpectedContent {
+                status,
+                content_type,
+                snippet,
+            } => NodestyError::UnexpectedContent {
+                status: *status,
+                content_type: content_type.clone(),
+                snippet: snippet.clone(),
+            },
+            NodestyError::CircuitOpen { group, retry_after } => NodestyError::CircuitOpen {
+                group: group.clone(),
+                retry_after: *retry_after,
+            },
+            NodestyError::Cancelled => NodestyError::Cancelled,
+        }
+    }
+
+    /// A short, stable name for the variant, suitable for log fields and metric labels.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodestyError::Config { .. } => "config",
+            NodestyError::Transport(_) => "transport",
+            NodestyError::Timeout => "timeout",
+            NodestyError::Encode { .. } => "encode",
+            NodestyError::Unauthorized { .. } => "unauthorized",
+            NodestyError::Forbidden { .. } => "forbidden",
+            NodestyError::NotFound { .. } => "not_found",
+            NodestyError::RateLimited { .. } => "rate_limited",
+            NodestyError::Validation { .. } => "validation",
+            NodestyError::Server { .. } => "server",
+            NodestyError::Api { .. } => "api",
+            NodestyError::Decode { .. } => "decode",
+            NodestyError::UnexpectedContent { .. } => "unexpected_content",
+            NodestyError::CircuitOpen { .. } => "circuit_open",
+            NodestyError::Cancelled => "cancelled",
+        }
+    }
+
+    /// The HTTP status the API answered with, if the error came from a response.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            NodestyError::Unauthorized { .. } => Some(StatusCode::UNAUTHORIZED),
+            NodestyError::Forbidden { .. } => Some(StatusCode::FORBIDDEN),
+            NodestyError::NotFound { .. } => Some(StatusCode::NOT_FOUND),
+            NodestyError::RateLimited { .. } => Some(StatusCode::TOO_MANY_REQUESTS),
+            NodestyError::Validation { status, .. } => Some(*status),
+            NodestyError::Server { status, .. } => Some(*status),
+            NodestyError::Api { status, .. } => *status,
+            NodestyError::UnexpectedContent { status, .. } => Some(*status),
+            NodestyError::Config { .. }
+            | NodestyError::Transport(_)
+            | NodestyError::Timeout
+            | NodestyError::Encode { .. }
+            | NodestyError::Decode { .. }
+            | NodestyError::CircuitOpen { .. }
+            | NodestyError::Cancelled => None,
+        }
+    }
+}
+
+/// Accepts both `{"errors": {"field": ["msg"]}}` and `{"errors": [{"field": .., "message": ..}]}`.
+fn validation_fields(body: &Value) -> HashMap<String, Vec<String>> {
+    let mut fields: HashMap<String, Vec<String>> = HashMap::new();
+
+    match &body["errors"] {
+        Value::Object(map) => {
+            for (field, messages) in map {
+                let messages = match messages {
+                    Value::Array(items) => items.iter().map(value_to_message).collect(),
+                    other => vec![value_to_message(other)],
+                };
+                fields.entry(field.clone()).or_default().extend(messages);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                let field = item["field"].as_str().unwrap_or_default().to_string();
+                fields.entry(field).or_default().push(value_to_message(&item["message"]));
+            }
+        }
+        _ => {}
+    }
+
+    fields
+}
+
+fn value_to_message(value: &Value) -> String {
+    match value {
+        Value::String(message) => message.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl fmt::Display for NodestyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn with_message(f: &mut fmt::Formatter<'_>, what: &str, message: &Option<String>) -> fmt::Result {
+            match message {
+                Some(message) => write!(f, "{}: {}", what, message),
+                None => f.write_str(what),
+            }
+        }
+
+        match self {
+            NodestyError::Config { field, message } => {
+                write!(f, "invalid configuration for `{}`: {}", field, message)
+            }
+            NodestyError::Transport(error) => write!(f, "transport error: {}", error),
+            NodestyError::Timeout => f.write_str("request timed out"),
+            NodestyError::Encode { message } => write!(f, "failed to encode request: {}", message),
+            NodestyError::Unauthorized { message } => with_message(f, "unauthorized", message),
+            NodestyError::Forbidden { message } => with_message(f, "forbidden", message),
+            NodestyError::NotFound { message } => with_message(f, "not found", message),
+            NodestyError::RateLimited { retry_after: Some(retry_after) } => {
+                write!(f, "rate limited, retry after {:?}", retry_after)
+            }
+            NodestyError::RateLimited { retry_after: None } => f.write_str("rate limited"),
+            NodestyError::Validation { message, fields, .. } => {
+                with_message(f, "validation failed", message)?;
+                let mut names: Vec<&String> = fields.keys().collect();
+                names.sort();
+                for name in names {
+                    write!(f, "; {}: {}", name, fields[name].join(", "))?;
+                }
+                Ok(())
+            }
+            NodestyError::Server { status, message } => {
+                with_message(f, &format!("server error ({})", status), message)
+            }
+            NodestyError::Api { status: Some(status), message } => {
+                with_message(f, &format!("api error ({})", status), message)
+            }
+            NodestyError::Api { status: None, message } => with_message(f, "api error", message),
+            NodestyError::Decode {
+                path: Some(path),
+                message,
+                ..
+            } => write!(f, "failed to decode response: {}: {}", path, message),
+            NodestyError::Decode { message, .. } => {
+                write!(f, "failed to decode response: {}", message)
+            }
+            NodestyError::UnexpectedContent {
+                status,
+                content_type,
+                snippet,
+            } => write!(
+                f,
+                "unexpected {} response ({}): {}",
+                content_type.as_deref().unwrap_or("non-JSON"),
+                status,
+                snippet
+            ),
+            NodestyError::CircuitOpen {
+                group,
+                retry_after: Some(retry_after),
+            } => write!(f, "circuit open for {}, retry after {:?}", group, retry_after),
+            NodestyError::CircuitOpen { group, retry_after: None } => {
+                write!(f, "circuit open for {}", group)
+            }
+            NodestyError::Cancelled => f.write_str("request cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for NodestyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NodestyError::Transport(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<TransportError> for NodestyError {
+    fn from(error: TransportError) -> Self {
+        if error.is_timeout() {
+            NodestyError::Timeout
+        } else {
+            NodestyError::Transport(error)
+        }
+    }
+}
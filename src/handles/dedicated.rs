@@ -0,0 +1,97 @@
+#[cfg(feature = "firewall")]
+use crate::handles::FirewallIpHandle;
+use crate::models::dedicated::{
+    DedicatedServerAction, DedicatedServerDetails, DedicatedServerHardwareComponent,
+    DedicatedServerOsTemplate, DedicatedServerReinstallData, DedicatedServerReinstallStatus,
+    DedicatedServerTask,
+};
+use crate::services::DedicatedServerApiService;
+#[cfg(feature = "firewall")]
+use crate::services::FirewallApiService;
+use crate::ApiCall;
+
+/// A single dedicated server. Obtained from [`DedicatedServerApiService::handle`] or
+/// [`Nodesty::dedicated_handle`](crate::Nodesty::dedicated_handle).
+#[derive(Debug, Clone)]
+pub struct DedicatedHandle {
+    service: DedicatedServerApiService,
+    id: String,
+}
+
+impl DedicatedHandle {
+    pub fn new(service: DedicatedServerApiService, id: impl Into<String>) -> Self {
+        Self {
+            service,
+            id: id.into(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn start(&self) -> ApiCall<'_, ()> {
+        self.perform_action(DedicatedServerAction::Start)
+    }
+
+    pub fn stop(&self) -> ApiCall<'_, ()> {
+        self.perform_action(DedicatedServerAction::Stop)
+    }
+
+    pub fn restart(&self) -> ApiCall<'_, ()> {
+        self.perform_action(DedicatedServerAction::Restart)
+    }
+
+    pub fn perform_action(
+        &self,
+        action: DedicatedServerAction,
+    ) -> ApiCall<'_, ()> {
+        self.service.perform_action(&self.id, action)
+    }
+
+    pub fn get_details(&self) -> ApiCall<'_, DedicatedServerDetails> {
+        self.service.get_details(&self.id)
+    }
+
+    pub fn get_hardware_components(
+        &self,
+    ) -> ApiCall<'_, Vec<DedicatedServerHardwareComponent>> {
+        self.service.get_hardware_components(&self.id)
+    }
+
+    pub fn get_os_templates(
+        &self,
+    ) -> ApiCall<'_, Vec<DedicatedServerOsTemplate>> {
+        self.service.get_os_templates(&self.id)
+    }
+
+    pub fn get_reinstall_status(
+        &self,
+    ) -> ApiCall<'_, DedicatedServerReinstallStatus> {
+        self.service.get_reinstall_status(&self.id)
+    }
+
+    pub fn reinstall(
+        &self,
+        data: DedicatedServerReinstallData,
+    ) -> ApiCall<'_, ()> {
+        self.service.reinstall(&self.id, data)
+    }
+
+    pub fn get_tasks(&self) -> ApiCall<'_, Vec<DedicatedServerTask>> {
+        self.service.get_tasks(&self.id)
+    }
+
+    /// The firewall of one of this server's IP addresses.
+    #[cfg(feature = "firewall")]
+    pub fn firewall(&self, ip: impl Into<String>) -> FirewallIpHandle {
+        FirewallIpHandle::new(FirewallApiService::new(self.service.client()), self.id.clone(), ip)
+    }
+}
+
+impl DedicatedServerApiService {
+    /// Binds `id` so it does not have to be passed to every call.
+    pub fn handle(&self, id: impl Into<String>) -> DedicatedHandle {
+        DedicatedHandle::new(self.clone(), id)
+    }
+}
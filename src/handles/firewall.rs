@@ -0,0 +1,97 @@
+use crate::models::firewall::{
+    AttackNotificationSettings, FirewallAttackLog, FirewallCreateRuleData, FirewallReverseDns,
+    FirewallRule, FirewallStatistics,
+};
+use crate::services::FirewallApiService;
+use crate::ApiCall;
+
+/// The firewall of one IP address of a service. Obtained from [`FirewallApiService::handle`],
+/// [`Nodesty::firewall_handle`](crate::Nodesty::firewall_handle) or a VPS or dedicated
+/// server handle.
+#[derive(Debug, Clone)]
+pub struct FirewallIpHandle {
+    service: FirewallApiService,
+    service_id: String,
+    ip: String,
+}
+
+impl FirewallIpHandle {
+    pub fn new(
+        service: FirewallApiService,
+        service_id: impl Into<String>,
+        ip: impl Into<String>,
+    ) -> Self {
+        Self {
+            service,
+            service_id: service_id.into(),
+            ip: ip.into(),
+        }
+    }
+
+    pub fn service_id(&self) -> &str {
+        &self.service_id
+    }
+
+    pub fn ip(&self) -> &str {
+        &self.ip
+    }
+
+    pub fn get_attack_logs(&self) -> ApiCall<'_, Vec<FirewallAttackLog>> {
+        self.service.get_attack_logs(&self.service_id, &self.ip)
+    }
+
+    pub fn get_attack_notification_settings(
+        &self,
+    ) -> ApiCall<'_, AttackNotificationSettings> {
+        self.service.get_attack_notification_settings(&self.service_id, &self.ip)
+    }
+
+    pub fn update_attack_notification_settings(
+        &self,
+        data: AttackNotificationSettings,
+    ) -> ApiCall<'_, AttackNotificationSettings> {
+        self.service
+            .update_attack_notification_settings(&self.service_id, &self.ip, data)
+    }
+
+    pub fn reset_reverse_dns(&self) -> ApiCall<'_, ()> {
+        self.service.reset_reverse_dns(&self.service_id, &self.ip)
+    }
+
+    pub fn get_reverse_dns(&self) -> ApiCall<'_, FirewallReverseDns> {
+        self.service.get_reverse_dns(&self.service_id, &self.ip)
+    }
+
+    pub fn upsert_reverse_dns(
+        &self,
+        data: FirewallReverseDns,
+    ) -> ApiCall<'_, ()> {
+        self.service.upsert_reverse_dns(&self.service_id, &self.ip, data)
+    }
+
+    pub fn delete_rule(&self, rule_id: u32) -> ApiCall<'_, ()> {
+        self.service.delete_rule(&self.service_id, &self.ip, rule_id)
+    }
+
+    pub fn get_rules(&self) -> ApiCall<'_, Vec<FirewallRule>> {
+        self.service.get_rules(&self.service_id, &self.ip)
+    }
+
+    pub fn create_rule(
+        &self,
+        data: FirewallCreateRuleData,
+    ) -> ApiCall<'_, ()> {
+        self.service.create_rule(&self.service_id, &self.ip, data)
+    }
+
+    pub fn get_statistics(&self) -> ApiCall<'_, Vec<FirewallStatistics>> {
+        self.service.get_statistics(&self.service_id, &self.ip)
+    }
+}
+
+impl FirewallApiService {
+    /// Binds `service_id` and `ip` so they do not have to be passed to every call.
+    pub fn handle(&self, service_id: impl Into<String>, ip: impl Into<String>) -> FirewallIpHandle {
+        FirewallIpHandle::new(self.clone(), service_id, ip)
+    }
+}
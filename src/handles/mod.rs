@@ -0,0 +1,20 @@
+//! Handles that bind a resource's identifiers once and expose its operations, for example
+//! `nodesty.vps_handle("123").restart().await`.
+
+#[cfg(feature = "dedicated")]
+pub mod dedicated;
+#[cfg(feature = "firewall")]
+pub mod firewall;
+#[cfg(feature = "user")]
+pub mod ticket;
+#[cfg(feature = "vps")]
+pub mod vps;
+
+#[cfg(feature = "dedicated")]
+pub use dedicated::DedicatedHandle;
+#[cfg(feature = "firewall")]
+pub use firewall::FirewallIpHandle;
+#[cfg(feature = "user")]
+pub use ticket::TicketHandle;
+#[cfg(feature = "vps")]
+pub use vps::VpsHandle;
@@ -0,0 +1,35 @@
+use crate::models::user::Ticket;
+use crate::services::UserApiService;
+use crate::ApiCall;
+
+/// A single support ticket. Obtained from [`UserApiService::ticket`] or
+/// [`Nodesty::ticket_handle`](crate::Nodesty::ticket_handle).
+#[derive(Debug, Clone)]
+pub struct TicketHandle {
+    service: UserApiService,
+    ticket_id: String,
+}
+
+impl TicketHandle {
+    pub fn new(service: UserApiService, ticket_id: impl Into<String>) -> Self {
+        Self {
+            service,
+            ticket_id: ticket_id.into(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.ticket_id
+    }
+
+    pub fn get(&self) -> ApiCall<'_, Ticket> {
+        self.service.get_ticket_by_id(&self.ticket_id)
+    }
+}
+
+impl UserApiService {
+    /// Binds `ticket_id` so it does not have to be passed to every call.
+    pub fn ticket(&self, ticket_id: impl Into<String>) -> TicketHandle {
+        TicketHandle::new(self.clone(), ticket_id)
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(feature = "firewall")]
+use crate::handles::FirewallIpHandle;
+use crate::models::vps::{
+    VpsAction, VpsBackup, VpsChangePasswordData, VpsDetails, VpsGraphs, VpsOsTemplate,
+    VpsReinstallData, VpsTask,
+};
+#[cfg(feature = "firewall")]
+use crate::services::FirewallApiService;
+use crate::services::VpsApiService;
+use crate::ApiCall;
+#[cfg(feature = "firewall")]
+use crate::NodestyError;
+
+/// A single VPS. Obtained from [`VpsApiService::handle`] or
+/// [`Nodesty::vps_handle`](crate::Nodesty::vps_handle).
+#[derive(Debug, Clone)]
+pub struct VpsHandle {
+    service: VpsApiService,
+    id: String,
+}
+
+impl VpsHandle {
+    pub fn new(service: VpsApiService, id: impl Into<String>) -> Self {
+        Self {
+            service,
+            id: id.into(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn start(&self) -> ApiCall<'_, ()> {
+        self.perform_action(VpsAction::Start)
+    }
+
+    pub fn stop(&self) -> ApiCall<'_, ()> {
+        self.perform_action(VpsAction::Stop)
+    }
+
+    pub fn restart(&self) -> ApiCall<'_, ()> {
+        self.perform_action(VpsAction::Restart)
+    }
+
+    pub fn power_off(&self) -> ApiCall<'_, ()> {
+        self.perform_action(VpsAction::PowerOff)
+    }
+
+    pub fn perform_action(&self, action: VpsAction) -> ApiCall<'_, ()> {
+        self.service.perform_action(&self.id, action)
+    }
+
+    pub fn restore_backup(&self, data: &VpsBackup) -> ApiCall<'_, ()> {
+        self.service.restore_backup(&self.id, data)
+    }
+
+    pub fn get_backups(&self) -> ApiCall<'_, Vec<VpsBackup>> {
+        self.service.get_backups(&self.id)
+    }
+
+    pub fn change_password(
+        &self,
+        data: VpsChangePasswordData,
+    ) -> ApiCall<'_, ()> {
+        self.service.change_password(&self.id, data)
+    }
+
+    pub fn get_usage_statistics(&self) -> ApiCall<'_, VpsGraphs> {
+        self.service.get_usage_statistics(&self.id)
+    }
+
+    pub fn get_details(&self) -> ApiCall<'_, VpsDetails> {
+        self.service.get_details(&self.id)
+    }
+
+    pub fn get_os_templates(&self) -> ApiCall<'_, Vec<VpsOsTemplate>> {
+        self.service.get_os_templates(&self.id)
+    }
+
+    pub fn reinstall(&self, data: VpsReinstallData) -> ApiCall<'_, ()> {
+        self.service.reinstall(&self.id, data)
+    }
+
+    pub fn get_tasks(&self) -> ApiCall<'_, Vec<VpsTask>> {
+        self.service.get_tasks(&self.id)
+    }
+
+    /// The firewall of one of this VPS's IP addresses.
+    #[cfg(feature = "firewall")]
+    pub fn firewall(&self, ip: impl Into<String>) -> FirewallIpHandle {
+        FirewallIpHandle::new(FirewallApiService::new(self.service.client()), self.id.clone(), ip)
+    }
+
+    /// Fetches the VPS details and returns a firewall handle for each of its IP addresses.
+    #[cfg(feature = "firewall")]
+    pub async fn firewalls(&self) -> Result<Vec<FirewallIpHandle>, NodestyError> {
+        let details = self.get_details().await?.into_result()?;
+        Ok(details.ips.into_iter().map(|ip| self.firewall(ip)).collect())
+    }
+}
+
+impl VpsApiService {
+    /// Binds `id` so it does not have to be passed to every call.
+    pub fn handle(&self, id: impl Into<String>) -> VpsHandle {
+        VpsHandle::new(self.clone(), id)
+    }
+}
@@ -0,0 +1,294 @@
+use crate::NodestyError;
+use serde::de::DeserializeOwned;
+use serde_path_to_error::Segment;
+use serde_json::Value;
+use std::fmt;
+
+/// Gives up after this many repairs, so a badly mismatched body does not cost a clone and a
+/// decode per field.
+const MAX_REPAIRS: usize = 64;
+
+/// Decodes `raw` into `T`, dropping values that do not fit the model instead of failing.
+///
+/// Each time decoding fails, the offending value is replaced by `null`, which an `Option`
+/// field accepts. If that fails as well the value is removed, which skips a bad element of a
+/// list or lets a `#[serde(default)]` field fall back to its default. A required field that
+/// cannot be repaired moves the failure to the object that holds it, and so on up to the
+/// root, in which case no data is returned.
+///
+/// Only failures found in `raw` itself are returned, in order, with their paths in `raw`.
+/// Failures caused by a repair, such as the field it removed now being missing, are not.
+pub(crate) fn decode<T: DeserializeOwned>(raw: &Value) -> (Option<T>, Vec<NodestyError>) {
+    let mut value = raw.clone();
+    let mut repairs = Repairs::default();
+    let mut errors = Vec::new();
+
+    for _ in 0..=MAX_REPAIRS {
+        let error = match serde_path_to_error::deserialize(&value) {
+            Ok(data) => return (Some(data), errors),
+            Err(error) => error,
+        };
+        let path: Vec<Key> = error.path().iter().map(Key::from).collect();
+        let message = error.into_inner().to_string();
+        let original = repairs.original(&path);
+
+        if !repairs.caused(&original, &message) {
+            errors.push(NodestyError::Decode {
+                path: Some(Path(&original).to_string()),
+                message,
+                body: None,
+            });
+        }
+        if !repairs.apply(&mut value, &path, &original) {
+            break;
+        }
+    }
+
+    (None, errors)
+}
+
+/// One step of a path. Enum variants and unknown segments have no counterpart in the JSON and
+/// are only kept for display.
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    Field(String),
+    Index(usize),
+    Other(String),
+}
+
+impl From<&Segment> for Key {
+    fn from(segment: &Segment) -> Self {
+        match segment {
+            Segment::Map { key } => Key::Field(key.clone()),
+            Segment::Seq { index } => Key::Index(*index),
+            Segment::Enum { variant } => Key::Other(variant.clone()),
+            Segment::Unknown => Key::Other("?".to_string()),
+        }
+    }
+}
+
+/// Formats a path the way `serde_path_to_error` does: `cpu.cores`, `[1].id`, or `.` for the root.
+struct Path<'a>(&'a [Key]);
+
+impl fmt::Display for Path<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str(".");
+        }
+        for (position, key) in self.0.iter().enumerate() {
+            match key {
+                Key::Index(index) => write!(f, "[{}]", index)?,
+                Key::Field(name) | Key::Other(name) => {
+                    if position > 0 {
+                        f.write_str(".")?;
+                    }
+                    f.write_str(name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The values replaced or removed so far, by their path in the original body.
+#[derive(Debug, Default)]
+struct Repairs {
+    nulled: Vec<Vec<Key>>,
+    removed: Vec<Vec<Key>>,
+}
+
+impl Repairs {
+    /// Maps a path in the repaired value back to the original body, accounting for list
+    /// elements removed in front of it.
+    fn original(&self, path: &[Key]) -> Vec<Key> {
+        let mut original = Vec::with_capacity(path.len());
+        for key in path {
+            let key = match key {
+                Key::Index(index) => {
+                    let mut removed: Vec<usize> = self
+                        .removed
+                        .iter()
+                        .filter_map(|removed| match removed.split_last() {
+                            Some((Key::Index(index), parent)) if parent == original.as_slice() => {
+                                Some(*index)
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    removed.sort_unstable();
+                    let mut index = *index;
+                    for removed in removed {
+                        if removed <= index {
+                            index += 1;
+                        }
+                    }
+                    Key::Index(index)
+                }
+                other => other.clone(),
+            };
+            original.push(key);
+        }
+        original
+    }
+
+    /// Whether a failure at `path` only exists because of an earlier repair: a value that was
+    /// replaced by `null`, or a field that was removed and is now missing.
+    fn caused(&self, path: &[Key], message: &str) -> bool {
+        if self.nulled.iter().any(|nulled| nulled == path) {
+            return true;
+        }
+        let missing = message
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.strip_suffix('`'));
+        missing.is_some_and(|field| {
+            let field = Key::Field(field.to_string());
+            self.removed.iter().any(|removed| removed.split_last() == Some((&field, path)))
+        })
+    }
+
+    /// Nulls the value at `path`, or removes it if it already is `null`. Returns `false` once
+    /// the failure reached the root and nothing is left to repair.
+    fn apply(&mut self, value: &mut Value, path: &[Key], original: &[Key]) -> bool {
+        // Only follow the part of the path that exists in the JSON.
+        let depth = (0..=path.len())
+            .rev()
+            .find(|depth| get(value, &path[..*depth]).is_some())
+            .unwrap_or_default();
+        let (path, original) = (&path[..depth], original[..depth].to_vec());
+
+        let Some((last, parent_path)) = path.split_last() else {
+            return false;
+        };
+        let mut parent = value;
+        for key in parent_path {
+            parent = step_mut(parent, key).expect("path was checked above");
+        }
+
+        let target = step_mut(parent, last).expect("path was checked above");
+        if !target.is_null() {
+            *target = Value::Null;
+            self.nulled.push(original);
+            return true;
+        }
+        match (parent, last) {
+            (Value::Object(map), Key::Field(key)) => {
+                map.remove(key);
+            }
+            (Value::Array(items), Key::Index(index)) => {
+                items.remove(*index);
+            }
+            _ => unreachable!("step_mut only follows objects and arrays"),
+        }
+        self.removed.push(original);
+        true
+    }
+}
+
+fn get<'a>(value: &'a Value, path: &[Key]) -> Option<&'a Value> {
+    path.iter().try_fold(value, step)
+}
+
+fn step<'a>(value: &'a Value, key: &Key) -> Option<&'a Value> {
+    match (value, key) {
+        (Value::Object(map), Key::Field(key)) => map.get(key),
+        (Value::Array(items), Key::Index(index)) => items.get(*index),
+        _ => None,
+    }
+}
+
+fn step_mut<'a>(value: &'a mut Value, key: &Key) -> Option<&'a mut Value> {
+    match (value, key) {
+        (Value::Object(map), Key::Field(key)) => map.get_mut(key),
+        (Value::Array(items), Key::Index(index)) => items.get_mut(*index),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Template {
+        id: u32,
+        name: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Service {
+        templates: Vec<Template>,
+        primary: Option<Template>,
+    }
+
+    fn errors(errors: &[NodestyError]) -> Vec<String> {
+        errors
+            .iter()
+            .map(|error| match error {
+                NodestyError::Decode { path, message, .. } => {
+                    format!("{}: {}", path.as_deref().unwrap_or_default(), message)
+                }
+                other => other.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bad_list_elements_are_reported_at_their_original_index() {
+        let raw = json!([{"id": "a"}, {"id": "b"}, {"id": 3}]);
+        let (data, found) = decode::<Vec<Template>>(&raw);
+
+        assert_eq!(
+            data,
+            Some(vec![Template {
+                id: 3,
+                name: None,
+                tags: Vec::new()
+            }])
+        );
+        assert_eq!(
+            errors(&found),
+            [
+                "[0].id: invalid type: string \"a\", expected u32",
+                "[1].id: invalid type: string \"b\", expected u32",
+            ]
+        );
+    }
+
+    #[test]
+    fn optional_and_default_fields_fall_back() {
+        let raw = json!({
+            "templates": [{"id": 1, "name": 5, "tags": "x"}],
+            "primary": {"name": "no id"}
+        });
+        let (data, found) = decode::<Service>(&raw);
+
+        let data = data.unwrap();
+        assert_eq!(data.templates[0].name, None);
+        assert!(data.templates[0].tags.is_empty());
+        assert_eq!(data.primary, None);
+        assert_eq!(
+            errors(&found),
+            [
+                "primary: missing field `id`",
+                "templates[0].name: invalid type: integer `5`, expected a string",
+                "templates[0].tags: invalid type: string \"x\", expected a sequence",
+            ]
+        );
+    }
+
+    #[test]
+    fn unrepairable_required_field_returns_no_data() {
+        let raw = json!({"templates": "none"});
+        let (data, found) = decode::<Service>(&raw);
+
+        assert_eq!(data, None);
+        assert_eq!(
+            errors(&found),
+            ["templates: invalid type: string \"none\", expected a sequence"]
+        );
+    }
+}
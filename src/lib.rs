@@ -1,7 +1,64 @@
+pub mod audit;
+pub mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cache;
+pub mod circuit_breaker;
 pub mod client;
+pub mod concurrency;
+mod config;
+pub mod dry_run;
+pub mod error;
+pub mod handles;
+mod lenient;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod middleware;
 pub mod models;
+pub mod nodesty;
+mod rate_limit;
+pub mod request;
+pub mod retry;
+pub mod route;
 pub mod services;
+mod single_flight;
+mod telemetry;
+#[cfg(feature = "rustls")]
+mod tls;
+pub mod transport;
 
-pub use client::NodestyApiClient;
+pub use audit::{AuditRecord, AuditSink, ChannelSink, JsonlFileSink};
+pub use auth::{
+    CommandTokenProvider, EnvTokenProvider, FileTokenProvider, StaticTokenProvider, TokenProvider,
+};
+pub use cache::CacheConfig;
+pub use circuit_breaker::{CircuitBreakerConfig, CircuitEvent, CircuitState};
+pub use client::{NodestyApiClient, NodestyApiClientBuilder};
+pub use concurrency::{Priority, WithPriority};
+pub use dry_run::PlannedMutation;
+pub use error::NodestyError;
+#[cfg(feature = "dedicated")]
+pub use handles::DedicatedHandle;
+#[cfg(feature = "firewall")]
+pub use handles::FirewallIpHandle;
+#[cfg(feature = "user")]
+pub use handles::TicketHandle;
+#[cfg(feature = "vps")]
+pub use handles::VpsHandle;
+#[cfg(feature = "metrics")]
+pub use metrics::ClientMetrics;
+pub use middleware::{Middleware, Next};
 pub use models::*;
-pub use services::*;
+pub use nodesty::Nodesty;
+pub use request::{ApiCall, CancellationToken, RequestOptions};
+pub use retry::RetryBudget;
+pub use route::Route;
+#[cfg(feature = "dedicated")]
+pub use services::DedicatedServerApiService;
+#[cfg(feature = "firewall")]
+pub use services::FirewallApiService;
+#[cfg(feature = "user")]
+pub use services::UserApiService;
+#[cfg(feature = "vps")]
+pub use services::VpsApiService;
+pub use transport::{HttpRequest, HttpResponse, ReqwestTransport, Transport, TransportError};
@@ -0,0 +1,181 @@
+use crate::circuit_breaker::{CircuitEvent, CircuitState};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    Opts, Registry, TextEncoder,
+};
+
+/// Prometheus collectors for every call made through a [`NodestyApiClient`](crate::NodestyApiClient).
+///
+/// Endpoints are labelled by their route template (`/services/{id}/vps/info`), so label
+/// cardinality does not grow with the number of services. Calls made with a plain path instead of
+/// a [`Route`](crate::Route) share the label `other`.
+#[derive(Debug, Clone)]
+pub struct ClientMetrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    errors: IntCounterVec,
+    duration: HistogramVec,
+    retries: IntCounterVec,
+    rate_limit_waits: IntCounter,
+    rate_limit_wait_seconds: Histogram,
+    circuit_state: IntGaugeVec,
+    circuit_transitions: IntCounterVec,
+    active_requests: IntGauge,
+    queue_depth: IntGauge,
+    queue_wait_seconds: HistogramVec,
+}
+
+impl ClientMetrics {
+    /// Creates the collectors in a fresh registry owned by the client.
+    pub fn new() -> Result<Self, prometheus::Error> {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Registers the collectors in `registry`, typically the one your service already exposes.
+    pub fn with_registry(registry: Registry) -> Result<Self, prometheus::Error> {
+        let requests = IntCounterVec::new(
+            Opts::new("nodesty_requests_total", "Nodesty API calls by final status."),
+            &["method", "endpoint", "status"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new("nodesty_request_errors_total", "Failed Nodesty API calls by error kind."),
+            &["method", "endpoint", "kind"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "nodesty_request_duration_seconds",
+                "Latency of Nodesty API calls, including retries.",
+            ),
+            &["method", "endpoint"],
+        )?;
+        let retries = IntCounterVec::new(
+            Opts::new("nodesty_retries_total", "Retried Nodesty API attempts."),
+            &["method", "endpoint"],
+        )?;
+        let rate_limit_waits = IntCounter::new(
+            "nodesty_rate_limit_waits_total",
+            "Requests held back by the client-side rate limiter.",
+        )?;
+        let rate_limit_wait_seconds = Histogram::with_opts(HistogramOpts::new(
+            "nodesty_rate_limit_wait_seconds",
+            "Time requests spent waiting for the rate-limit window to reset.",
+        ))?;
+
+        let circuit_state = IntGaugeVec::new(
+            Opts::new(
+                "nodesty_circuit_state",
+                "Circuit breaker state by endpoint group: 0 closed, 1 open, 2 half-open.",
+            ),
+            &["group"],
+        )?;
+        let circuit_transitions = IntCounterVec::new(
+            Opts::new("nodesty_circuit_transitions_total", "Circuit breaker state changes."),
+            &["group", "state"],
+        )?;
+        let active_requests = IntGauge::new(
+            "nodesty_active_requests",
+            "Attempts holding one of the client's concurrency slots.",
+        )?;
+        let queue_depth = IntGauge::new(
+            "nodesty_queue_depth",
+            "Attempts waiting for a concurrency slot.",
+        )?;
+        let queue_wait_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "nodesty_queue_wait_seconds",
+                "Time attempts spent waiting for a concurrency slot.",
+            ),
+            &["priority"],
+        )?;
+
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        registry.register(Box::new(retries.clone()))?;
+        registry.register(Box::new(rate_limit_waits.clone()))?;
+        registry.register(Box::new(rate_limit_wait_seconds.clone()))?;
+        registry.register(Box::new(circuit_state.clone()))?;
+        registry.register(Box::new(circuit_transitions.clone()))?;
+        registry.register(Box::new(active_requests.clone()))?;
+        registry.register(Box::new(queue_depth.clone()))?;
+        registry.register(Box::new(queue_wait_seconds.clone()))?;
+
+        Ok(Self {
+            registry,
+            requests,
+            errors,
+            duration,
+            retries,
+            rate_limit_waits,
+            rate_limit_wait_seconds,
+            circuit_state,
+            circuit_transitions,
+            active_requests,
+            queue_depth,
+            queue_wait_seconds,
+        })
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Renders the registry in the Prometheus text exposition format, ready to be served
+    /// from a `/metrics` endpoint.
+    pub fn encode_text(&self) -> String {
+        let mut buffer = Vec::new();
+        // Encoding into a Vec only fails on malformed metric families, which we never produce.
+        let _ = TextEncoder::new().encode(&self.registry.gather(), &mut buffer);
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+
+    pub(crate) fn observe_request(
+        &self,
+        method: &str,
+        endpoint: &str,
+        status: Option<u16>,
+        error_kind: Option<&str>,
+        seconds: f64,
+    ) {
+        let status = status.map_or_else(|| "none".to_string(), |status| status.to_string());
+        self.requests
+            .with_label_values(&[method, endpoint, &status])
+            .inc();
+        self.duration
+            .with_label_values(&[method, endpoint])
+            .observe(seconds);
+        if let Some(kind) = error_kind {
+            self.errors.with_label_values(&[method, endpoint, kind]).inc();
+        }
+    }
+
+    pub(crate) fn observe_retry(&self, method: &str, endpoint: &str) {
+        self.retries.with_label_values(&[method, endpoint]).inc();
+    }
+
+    pub(crate) fn observe_rate_limit_wait(&self, seconds: f64) {
+        self.rate_limit_waits.inc();
+        self.rate_limit_wait_seconds.observe(seconds);
+    }
+
+    pub(crate) fn observe_concurrency(&self, active: usize, queued: usize) {
+        self.active_requests.set(active as i64);
+        self.queue_depth.set(queued as i64);
+    }
+
+    pub(crate) fn observe_queue_wait(&self, priority: &str, seconds: f64) {
+        self.queue_wait_seconds.with_label_values(&[priority]).observe(seconds);
+    }
+
+    pub(crate) fn observe_circuit_change(&self, event: &CircuitEvent) {
+        let state = match event.to {
+            CircuitState::Closed => 0,
+            CircuitState::Open => 1,
+            CircuitState::HalfOpen => 2,
+        };
+        self.circuit_state.with_label_values(&[&event.group]).set(state);
+        self.circuit_transitions
+            .with_label_values(&[event.group.as_str(), event.to.as_str()])
+            .inc();
+    }
+}
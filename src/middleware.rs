@@ -0,0 +1,42 @@
+use crate::transport::{BoxFuture, HttpRequest, HttpResponse, Transport};
+use crate::NodestyError;
+use std::sync::Arc;
+
+/// Wraps every attempt a [`NodestyApiClient`](crate::NodestyApiClient) makes, including retries.
+///
+/// A middleware can inspect or rewrite the outgoing [`HttpRequest`], inspect or rewrite the
+/// [`HttpResponse`] returned by [`Next::run`], or skip `next` altogether and answer the request
+/// itself. Middlewares run in the order they were added to the builder; the first one added
+/// sees the request first and the response last.
+pub trait Middleware: Send + Sync {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, NodestyError>>;
+}
+
+/// The rest of the middleware chain, ending with the transport.
+pub struct Next<'a> {
+    middlewares: &'a [Arc<dyn Middleware>],
+    transport: &'a dyn Transport,
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(middlewares: &'a [Arc<dyn Middleware>], transport: &'a dyn Transport) -> Self {
+        Self {
+            middlewares,
+            transport,
+        }
+    }
+
+    pub fn run(self, request: HttpRequest) -> BoxFuture<'a, Result<HttpResponse, NodestyError>> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => middleware.handle(request, Next::new(rest, self.transport)),
+            None => {
+                let transport = self.transport;
+                Box::pin(async move { transport.send(request).await.map_err(NodestyError::from) })
+            }
+        }
+    }
+}
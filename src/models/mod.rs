@@ -1,11 +1,19 @@
+#[cfg(feature = "dedicated")]
 pub mod dedicated;
+#[cfg(feature = "firewall")]
 pub mod firewall;
 pub mod shared;
+#[cfg(feature = "user")]
 pub mod user;
+#[cfg(feature = "vps")]
 pub mod vps;
 
+#[cfg(feature = "dedicated")]
 pub use dedicated::*;
+#[cfg(feature = "firewall")]
 pub use firewall::*;
 pub use shared::*;
+#[cfg(feature = "user")]
 pub use user::*;
+#[cfg(feature = "vps")]
 pub use vps::*;
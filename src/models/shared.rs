@@ -1,4 +1,10 @@
+use crate::cache::CacheConfig;
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::retry::RetryBudget;
+use crate::NodestyError;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -9,34 +15,196 @@ pub struct ApiResponse<T> {
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<T>,
+    /// The undecoded response body. Only set in lenient decoding mode when the body did not
+    /// fully match the model, so callers can still pick out the fields that were dropped.
+    #[serde(skip)]
+    pub raw: Option<Value>,
+}
+
+impl<T> ApiResponse<T> {
+    /// Converts the envelope into its payload so `?` can be used end to end:
+    /// `vps.get_details(id).await?.into_result()?`.
+    pub fn into_result(self) -> Result<T, NodestyError> {
+        match (self.success, self.data) {
+            (true, Some(data)) => Ok(data),
+            (true, None) => Err(NodestyError::Decode {
+                path: None,
+                message: self.error.unwrap_or_else(|| "response contained no data".to_string()),
+                body: self.raw.map(|raw| raw.to_string()),
+            }),
+            (false, _) => Err(NodestyError::Api {
+                status: None,
+                message: self.error,
+            }),
+        }
+    }
 }
 
 fn default_success() -> bool {
     true
 }
 
+/// A personal access token. Its `Debug` and `Display` output is redacted so it never
+/// ends up in logs; use [`expose`](Self::expose) to read the actual value.
+#[derive(Clone, PartialEq, Eq)]
+pub struct AccessToken(String);
+
+impl AccessToken {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AccessToken(***)")
+    }
+}
+
+impl fmt::Display for AccessToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl From<String> for AccessToken {
+    fn from(token: String) -> Self {
+        Self(token)
+    }
+}
+
+impl From<&str> for AccessToken {
+    fn from(token: &str) -> Self {
+        Self(token.to_string())
+    }
+}
+
+/// An HTTP, HTTPS or SOCKS5 proxy every request is sent through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// `http://`, `https://`, `socks5://` or `socks5h://` URL. SOCKS requires the `socks` feature.
+    pub url: String,
+    /// Username and password sent as proxy basic auth. Prefer this over credentials in `url`,
+    /// which would show up in `Debug` output.
+    pub basic_auth: Option<(String, AccessToken)>,
+    /// Comma-separated hosts, domains and CIDR blocks to reach directly, as in `NO_PROXY`.
+    pub no_proxy: Option<String>,
+}
+
+impl ProxyConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            basic_auth: None,
+            no_proxy: None,
+        }
+    }
+
+    pub fn with_basic_auth(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<AccessToken>,
+    ) -> Self {
+        self.basic_auth = Some((username.into(), password.into()));
+        self
+    }
+
+    pub fn with_no_proxy(mut self, no_proxy: impl Into<String>) -> Self {
+        self.no_proxy = Some(no_proxy.into());
+        self
+    }
+}
+
+impl From<String> for ProxyConfig {
+    fn from(url: String) -> Self {
+        Self::new(url)
+    }
+}
+
+impl From<&str> for ProxyConfig {
+    fn from(url: &str) -> Self {
+        Self::new(url)
+    }
+}
+
+/// A client certificate presented for mutual TLS. `Debug` output never includes the key.
+#[derive(Clone, PartialEq, Eq)]
+pub enum ClientIdentity {
+    /// PEM-encoded certificate chain and PKCS#8 private key. With only the `rustls` feature
+    /// enabled, RSA and SEC1 keys are accepted as well.
+    Pem {
+        certificate_chain: Vec<u8>,
+        private_key: Vec<u8>,
+    },
+    /// A DER-encoded PKCS#12 archive. Requires the `native-tls` feature.
+    Pkcs12 { der: Vec<u8>, password: AccessToken },
+}
+
+impl fmt::Debug for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientIdentity::Pem { .. } => f.write_str("ClientIdentity::Pem(***)"),
+            ClientIdentity::Pkcs12 { .. } => f.write_str("ClientIdentity::Pkcs12(***)"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RestClientOptions {
-    pub access_token: String,
+    pub access_token: AccessToken,
     pub base_url: String,
     pub retry: Option<u32>,
+    pub retry_base_delay_ms: Option<u64>,
+    pub retry_max_delay_ms: Option<u64>,
+    pub retry_budget: Option<RetryBudget>,
     pub timeout_ms: Option<u64>,
     pub rate_limit_offset_ms: Option<u64>,
+    pub lenient_decoding: bool,
+    pub idempotency_keys: bool,
+    pub dry_run: bool,
+    pub proxy: Option<ProxyConfig>,
+    /// PEM-encoded certificates trusted in addition to the built-in roots.
+    pub root_certificates: Vec<Vec<u8>>,
+    pub client_identity: Option<ClientIdentity>,
+    /// Base64 SHA-256 hashes of the SubjectPublicKeyInfo of certificates the server may present.
+    pub pinned_spki_sha256: Vec<String>,
+    pub cache: Option<CacheConfig>,
+    pub coalesce_requests: bool,
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    pub max_concurrency: Option<usize>,
 }
 
 impl RestClientOptions {
-    pub fn new(access_token: String) -> Self {
+    pub fn new(access_token: impl Into<AccessToken>) -> Self {
         Self {
-            access_token,
+            access_token: access_token.into(),
             base_url: "https://nodesty.com/api".to_string(),
             retry: Some(3),
+            retry_base_delay_ms: Some(200),
+            retry_max_delay_ms: Some(10_000),
+            retry_budget: Some(RetryBudget::default()),
             timeout_ms: Some(30_000),
             rate_limit_offset_ms: Some(50),
+            lenient_decoding: false,
+            idempotency_keys: true,
+            dry_run: false,
+            proxy: None,
+            root_certificates: Vec::new(),
+            client_identity: None,
+            pinned_spki_sha256: Vec::new(),
+            cache: None,
+            coalesce_requests: true,
+            circuit_breaker: None,
+            max_concurrency: None,
         }
     }
 
-    pub fn with_base_url(mut self, base_url: String) -> Self {
-        self.base_url = base_url;
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
         self
     }
 
@@ -45,6 +213,22 @@ impl RestClientOptions {
         self
     }
 
+    pub fn with_retry_delay_ms(mut self, base_delay_ms: u64, max_delay_ms: u64) -> Self {
+        self.retry_base_delay_ms = Some(base_delay_ms);
+        self.retry_max_delay_ms = Some(max_delay_ms);
+        self
+    }
+
+    pub fn with_retry_budget(mut self, retry_budget: RetryBudget) -> Self {
+        self.retry_budget = Some(retry_budget);
+        self
+    }
+
+    pub fn without_retry_budget(mut self) -> Self {
+        self.retry_budget = None;
+        self
+    }
+
     pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
         self.timeout_ms = Some(timeout_ms);
         self
@@ -54,4 +238,82 @@ impl RestClientOptions {
         self.rate_limit_offset_ms = Some(rate_limit_offset_ms);
         self
     }
+
+    /// When enabled, a response that does not match its model is decoded as far as possible
+    /// instead of failing: values that do not fit become `None` for optional fields, fall back
+    /// to the default for `#[serde(default)]` fields and are skipped in lists. `data` holds the
+    /// result, `error` lists every dropped value with its JSON path and `raw` holds the body.
+    /// `data` is `None` only if a required field could not be repaired.
+    pub fn with_lenient_decoding(mut self, lenient_decoding: bool) -> Self {
+        self.lenient_decoding = lenient_decoding;
+        self
+    }
+
+    /// When enabled (the default), every POST carries a generated `Idempotency-Key` that stays
+    /// the same across its retries, which makes POSTs safe to retry.
+    pub fn with_idempotency_keys(mut self, idempotency_keys: bool) -> Self {
+        self.idempotency_keys = idempotency_keys;
+        self
+    }
+
+    /// In dry-run mode reads are sent as usual, while POST, PUT, PATCH and DELETE calls are only
+    /// recorded and report success with no data. Read the recorded calls back with
+    /// [`NodestyApiClient::planned_mutations`](crate::NodestyApiClient::planned_mutations).
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: impl Into<ProxyConfig>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Trusts the certificates in `pem` (one or more) in addition to the built-in roots,
+    /// for example a corporate CA that re-signs traffic at an egress proxy.
+    pub fn with_root_certificate_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    pub fn with_client_identity(mut self, client_identity: ClientIdentity) -> Self {
+        self.client_identity = Some(client_identity);
+        self
+    }
+
+    /// Rejects the connection unless a certificate in the server's chain has a public key
+    /// whose SHA-256 hash matches one of the pins. Pins are base64, optionally prefixed with
+    /// `sha256/` as printed by `openssl`/`curl` tooling. Requires the `rustls` feature, since
+    /// the check runs during the handshake, before the token is sent.
+    pub fn with_pinned_spki_sha256(mut self, pin: impl Into<String>) -> Self {
+        self.pinned_spki_sha256.push(pin.into());
+        self
+    }
+
+    /// Caches GET responses as described in [`CacheConfig`]. Off by default.
+    pub fn with_cache(mut self, cache: CacheConfig) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// When enabled (the default), concurrent identical GETs share one request and each
+    /// caller decodes the shared response.
+    pub fn with_request_coalescing(mut self, coalesce_requests: bool) -> Self {
+        self.coalesce_requests = coalesce_requests;
+        self
+    }
+
+    /// Guards each endpoint group with a circuit breaker as described in
+    /// [`CircuitBreakerConfig`]. Off by default.
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Lets at most `max_concurrency` requests be in flight at once. Further calls queue by
+    /// [`Priority`](crate::Priority). Unlimited by default.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
 }
@@ -0,0 +1,94 @@
+#[cfg(feature = "dedicated")]
+use crate::handles::DedicatedHandle;
+#[cfg(feature = "firewall")]
+use crate::handles::FirewallIpHandle;
+#[cfg(feature = "user")]
+use crate::handles::TicketHandle;
+#[cfg(feature = "vps")]
+use crate::handles::VpsHandle;
+#[cfg(feature = "dedicated")]
+use crate::services::DedicatedServerApiService;
+#[cfg(feature = "firewall")]
+use crate::services::FirewallApiService;
+#[cfg(feature = "user")]
+use crate::services::UserApiService;
+#[cfg(feature = "vps")]
+use crate::services::VpsApiService;
+use crate::{NodestyApiClient, NodestyError, RestClientOptions};
+use std::sync::Arc;
+
+/// Entry point that owns one [`NodestyApiClient`] and hands out the services built on it.
+///
+/// Cloning is cheap and every clone shares the same connection pool, rate limiter and
+/// retry budget, so a `Nodesty` can be stored directly in application state.
+#[derive(Debug, Clone)]
+pub struct Nodesty {
+    client: Arc<NodestyApiClient>,
+}
+
+impl Nodesty {
+    pub fn new(options: RestClientOptions) -> Result<Self, NodestyError> {
+        Ok(Self::from_client(NodestyApiClient::new(options)?))
+    }
+
+    /// Wraps a client built with [`NodestyApiClient::builder`], or one that is already shared.
+    pub fn from_client(client: impl Into<Arc<NodestyApiClient>>) -> Self {
+        Self {
+            client: client.into(),
+        }
+    }
+
+    pub fn client(&self) -> &Arc<NodestyApiClient> {
+        &self.client
+    }
+
+    #[cfg(feature = "user")]
+    pub fn user(&self) -> UserApiService {
+        UserApiService::new(self.client.clone())
+    }
+
+    #[cfg(feature = "vps")]
+    pub fn vps(&self) -> VpsApiService {
+        VpsApiService::new(self.client.clone())
+    }
+
+    #[cfg(feature = "dedicated")]
+    pub fn dedicated(&self) -> DedicatedServerApiService {
+        DedicatedServerApiService::new(self.client.clone())
+    }
+
+    #[cfg(feature = "firewall")]
+    pub fn firewall(&self) -> FirewallApiService {
+        FirewallApiService::new(self.client.clone())
+    }
+
+    #[cfg(feature = "vps")]
+    pub fn vps_handle(&self, id: impl Into<String>) -> VpsHandle {
+        self.vps().handle(id)
+    }
+
+    #[cfg(feature = "dedicated")]
+    pub fn dedicated_handle(&self, id: impl Into<String>) -> DedicatedHandle {
+        self.dedicated().handle(id)
+    }
+
+    #[cfg(feature = "firewall")]
+    pub fn firewall_handle(
+        &self,
+        service_id: impl Into<String>,
+        ip: impl Into<String>,
+    ) -> FirewallIpHandle {
+        self.firewall().handle(service_id, ip)
+    }
+
+    #[cfg(feature = "user")]
+    pub fn ticket_handle(&self, ticket_id: impl Into<String>) -> TicketHandle {
+        self.user().ticket(ticket_id)
+    }
+}
+
+impl From<NodestyApiClient> for Nodesty {
+    fn from(client: NodestyApiClient) -> Self {
+        Self::from_client(client)
+    }
+}
@@ -0,0 +1,263 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const REMAINING_HEADERS: [&str; 2] = ["x-ratelimit-remaining", "ratelimit-remaining"];
+const RESET_HEADERS: [&str; 2] = ["x-ratelimit-reset", "ratelimit-reset"];
+
+/// Upper bound for any wait taken from response headers, so a bogus value such as
+/// `Retry-After: 1e30` cannot stall the client indefinitely.
+const MAX_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Rate-limit state shared by every request made through one client.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    offset: Duration,
+    state: Mutex<RateLimitState>,
+}
+
+#[derive(Debug, Default)]
+struct RateLimitState {
+    remaining: Option<u64>,
+    reset_at: Option<Instant>,
+    blocked_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(offset: Duration) -> Self {
+        Self {
+            offset,
+            state: Mutex::new(RateLimitState::default()),
+        }
+    }
+
+    /// Waits until the limiter allows another request and reserves one slot of the window.
+    /// Returns how long the caller was held back.
+    pub(crate) async fn acquire(&self) -> Duration {
+        let started = Instant::now();
+        let mut waited = false;
+
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+
+                if state.reset_at.is_some_and(|reset_at| reset_at <= now) {
+                    state.remaining = None;
+                    state.reset_at = None;
+                }
+                if state.blocked_until.is_some_and(|until| until <= now) {
+                    state.blocked_until = None;
+                }
+
+                match (state.blocked_until, state.remaining, state.reset_at) {
+                    (Some(until), _, _) => Some(until - now + self.offset),
+                    (None, Some(0), Some(reset_at)) => Some(reset_at - now + self.offset),
+                    // A 429 without a reset header only blocks until its `Retry-After` passed;
+                    // after that the window is unknown again.
+                    (None, Some(0), None) => {
+                        state.remaining = None;
+                        None
+                    }
+                    (None, Some(remaining), _) => {
+                        state.remaining = Some(remaining.saturating_sub(1));
+                        None
+                    }
+                    (None, None, _) => None,
+                }
+            };
+
+            match wait {
+                Some(wait) => {
+                    tokio::time::sleep(wait).await;
+                    waited = true;
+                }
+                None if waited => return started.elapsed(),
+                None => return Duration::ZERO,
+            }
+        }
+    }
+
+    pub(crate) fn update(&self, status: StatusCode, headers: &HeaderMap) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(remaining) = header_u64(headers, &REMAINING_HEADERS) {
+            state.remaining = Some(remaining);
+        }
+        if let Some(reset) = header_f64(headers, &RESET_HEADERS) {
+            state.reset_at = Some(now + reset_delay(reset));
+        }
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let delay = retry_after(headers)
+                .or_else(|| state.reset_at.map(|reset_at| reset_at.saturating_duration_since(now)))
+                .unwrap_or(Duration::from_secs(1));
+            state.blocked_until = Some(now + delay);
+            state.remaining = Some(0);
+        }
+    }
+
+    /// Returns `true` while requests are being held back by a 429 or an exhausted window.
+    pub(crate) fn is_limited(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        let now = Instant::now();
+        state.blocked_until.is_some_and(|until| until > now)
+            || (state.remaining == Some(0) && state.reset_at.is_some_and(|reset_at| reset_at > now))
+    }
+}
+
+/// Reads `Retry-After` as either delay-seconds or an HTTP-date. Only the IMF-fixdate form
+/// (`Sun, 06 Nov 1994 08:49:37 GMT`) is understood; the obsolete RFC 850 and asctime forms
+/// are ignored.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(secs) = header_f64(headers, &[RETRY_AFTER.as_str()]) {
+        return Some(capped_secs(secs));
+    }
+    let date = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    let at = parse_http_date(date)?;
+    Some(capped_secs(at as f64 - unix_now()))
+}
+
+/// `X-RateLimit-Reset` is either a delay in seconds or a Unix timestamp, depending on the edge.
+fn reset_delay(reset: f64) -> Duration {
+    if reset > 1_000_000_000.0 {
+        capped_secs(reset - unix_now())
+    } else {
+        capped_secs(reset)
+    }
+}
+
+fn capped_secs(secs: f64) -> Duration {
+    Duration::try_from_secs_f64(secs.max(0.0)).map_or(MAX_WAIT, |wait| wait.min(MAX_WAIT))
+}
+
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default()
+}
+
+/// Parses an IMF-fixdate into seconds since the Unix epoch.
+fn parse_http_date(date: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let mut parts = date.trim().split_ascii_whitespace();
+    let _weekday = parts.next()?.strip_suffix(',')?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    let month_name = parts.next()?;
+    let month = MONTHS.iter().position(|name| *name == month_name)? as i64 + 1;
+    let year = parts.next().filter(|year| year.len() == 4)?;
+    let year: i64 = year.parse().ok()?;
+    let mut time = parts.next()?.split(':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (time.next()??, time.next()??, time.next()??);
+    if parts.next()? != "GMT" || parts.next().is_some() || time.next().is_some() {
+        return None;
+    }
+    if !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Converts a civil date to days since 1970-01-01 (Howard Hinnant's algorithm).
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+fn header_u64(headers: &HeaderMap, names: &[&str]) -> Option<u64> {
+    names
+        .iter()
+        .find_map(|name| headers.get(*name)?.to_str().ok()?.trim().parse().ok())
+}
+
+fn header_f64(headers: &HeaderMap, names: &[&str]) -> Option<f64> {
+    names.iter().find_map(|name| {
+        headers
+            .get(*name)?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn too_many_requests_without_reset_header_does_not_underflow() {
+        let limiter = RateLimiter::new(Duration::ZERO);
+        limiter.update(StatusCode::TOO_MANY_REQUESTS, &headers(&[("retry-after", "0")]));
+
+        assert_eq!(limiter.acquire().await, Duration::ZERO);
+        assert_eq!(limiter.acquire().await, Duration::ZERO);
+        assert!(!limiter.is_limited());
+    }
+
+    #[tokio::test]
+    async fn too_many_requests_blocks_for_retry_after() {
+        let limiter = RateLimiter::new(Duration::ZERO);
+        limiter.update(StatusCode::TOO_MANY_REQUESTS, &headers(&[("retry-after", "0.05")]));
+
+        assert!(limiter.is_limited());
+        assert!(limiter.acquire().await >= Duration::from_millis(50));
+        assert!(!limiter.is_limited());
+    }
+
+    #[tokio::test]
+    async fn remaining_requests_are_consumed() {
+        let limiter = RateLimiter::new(Duration::ZERO);
+        limiter.update(
+            StatusCode::OK,
+            &headers(&[("x-ratelimit-remaining", "2"), ("x-ratelimit-reset", "60")]),
+        );
+
+        assert_eq!(limiter.acquire().await, Duration::ZERO);
+        assert!(!limiter.is_limited());
+        assert_eq!(limiter.acquire().await, Duration::ZERO);
+        assert!(limiter.is_limited());
+    }
+
+    #[test]
+    fn huge_waits_are_capped() {
+        assert_eq!(retry_after(&headers(&[("retry-after", "1e30")])), Some(MAX_WAIT));
+        assert_eq!(retry_after(&headers(&[("retry-after", "-5")])), Some(Duration::ZERO));
+        assert_eq!(reset_delay(1e300), MAX_WAIT);
+        assert_eq!(reset_delay(30.0), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn retry_after_accepts_http_dates() {
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(784_111_777));
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37"), None);
+
+        let past = headers(&[("retry-after", "Sun, 06 Nov 1994 08:49:37 GMT")]);
+        assert_eq!(retry_after(&past), Some(Duration::ZERO));
+        let future = headers(&[("retry-after", "Fri, 31 Dec 9999 23:59:59 GMT")]);
+        assert_eq!(retry_after(&future), Some(MAX_WAIT));
+        assert_eq!(retry_after(&headers(&[("retry-after", "soon")])), None);
+    }
+}
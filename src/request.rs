@@ -0,0 +1,257 @@
+use crate::concurrency::Priority;
+use crate::transport::BoxFuture;
+use crate::{ApiResponse, NodestyApiClient, NodestyError, Route};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+use std::future::{poll_fn, Future, IntoFuture};
+use std::marker::PhantomData;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Settings for a single call that override or extend the client-wide ones.
+#[derive(Debug, Clone, Default)]
+pub struct RequestOptions {
+    /// Replaces the client's `timeout_ms` for each attempt of this call.
+    pub timeout: Option<Duration>,
+    /// Sent in addition to the client's headers. `Authorization` is always set by the client.
+    pub headers: HeaderMap,
+    /// Appended to the path, for example a time range for `get_statistics`.
+    pub query: Vec<(String, String)>,
+    /// Replaces the priority of the enclosing [`Priority::scope`].
+    pub priority: Option<Priority>,
+    pub cancellation: Option<CancellationToken>,
+    /// Sent as the `Idempotency-Key` header instead of a generated key.
+    pub idempotency_key: Option<String>,
+    /// Recorded as the actor of this call in the audit log instead of the client's actor.
+    pub actor: Option<String>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    pub fn with_query(mut self, name: impl Into<String>, value: impl fmt::Display) -> Self {
+        self.query.push((name.into(), value.to_string()));
+        self
+    }
+
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Aborts the call with [`NodestyError::Cancelled`] once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Uses `key` as the `Idempotency-Key`, for example one derived from a job ID so that a
+    /// restarted job does not repeat a mutation the API already applied.
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+}
+
+/// Cancels every call it was passed to. Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<CancellationState>,
+}
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Completes once the token is cancelled.
+    pub async fn cancelled(&self) {
+        let mut notified = pin!(self.inner.notify.notified());
+        // Registers as a waiter before checking the flag, so a concurrent `cancel` is not missed.
+        notified.as_mut().enable();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// Runs `future` unless `token` is cancelled first. Dropping the future releases everything
+/// it held, such as its concurrency slot or its place in a coalesced request.
+pub(crate) async fn cancellable<T>(
+    future: impl Future<Output = Result<T, NodestyError>>,
+    token: Option<&CancellationToken>,
+) -> Result<T, NodestyError> {
+    let Some(token) = token else {
+        return future.await;
+    };
+
+    let mut future = pin!(future);
+    let mut cancelled = pin!(token.cancelled());
+    poll_fn(|cx| {
+        if cancelled.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Err(NodestyError::Cancelled));
+        }
+        future.as_mut().poll(cx)
+    })
+    .await
+}
+
+/// A pending API call, returned by every service method. Await it directly, or adjust it
+/// first:
+///
+/// ```ignore
+/// let stats = firewall
+///     .get_statistics(service_id, ip)
+///     .query("from", "2024-01-01")
+///     .timeout(Duration::from_secs(5))
+///     .await?;
+/// ```
+#[must_use = "API calls do nothing unless awaited"]
+pub struct ApiCall<'a, T> {
+    client: &'a NodestyApiClient,
+    method: Method,
+    route: Route,
+    body: Option<Value>,
+    retry_safe: bool,
+    options: RequestOptions,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for ApiCall<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiCall")
+            .field("method", &self.method)
+            .field("route", &self.route)
+            .field("retry_safe", &self.retry_safe)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T> ApiCall<'a, T> {
+    pub(crate) fn new(
+        client: &'a NodestyApiClient,
+        method: Method,
+        route: Route,
+        body: Option<Value>,
+        retry_safe: bool,
+    ) -> Self {
+        Self {
+            client,
+            method,
+            route,
+            body,
+            retry_safe,
+            options: RequestOptions::default(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Replaces all per-call settings with `options`.
+    pub fn options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = Some(timeout);
+        self
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.options.headers.append(name, value);
+        self
+    }
+
+    pub fn query(mut self, name: impl Into<String>, value: impl fmt::Display) -> Self {
+        self.options.query.push((name.into(), value.to_string()));
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.options.priority = Some(priority);
+        self
+    }
+
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.options.cancellation = Some(token);
+        self
+    }
+
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.options.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Records `actor`, for example the user who clicked "restart", in the audit log.
+    pub fn actor(mut self, actor: impl Into<String>) -> Self {
+        self.options.actor = Some(actor.into());
+        self
+    }
+
+    /// Retries transient failures regardless of the method. Only use this for calls the API
+    /// is known to handle idempotently.
+    pub fn retryable(mut self) -> Self {
+        self.retry_safe = true;
+        self
+    }
+}
+
+impl<'a, T: DeserializeOwned + 'a> IntoFuture for ApiCall<'a, T> {
+    type Output = Result<ApiResponse<T>, NodestyError>;
+    type IntoFuture = BoxFuture<'a, Self::Output>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let mut route = self.route;
+        for (name, value) in &self.options.query {
+            route = route.query(name, value);
+        }
+        Box::pin(self.client.execute(
+            self.method,
+            route,
+            self.body,
+            self.retry_safe,
+            self.options,
+        ))
+    }
+}
@@ -0,0 +1,218 @@
+use crate::NodestyError;
+use reqwest::header::HeaderName;
+use reqwest::{Method, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Client-wide cap on retries. Every retry spends one token and every request that
+/// succeeds on its first attempt deposits `retry_ratio` tokens back, so a struggling
+/// API cannot be hammered with retry storms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryBudget {
+    pub max_tokens: u32,
+    pub retry_ratio: f64,
+}
+
+impl RetryBudget {
+    pub fn new(max_tokens: u32, retry_ratio: f64) -> Self {
+        Self {
+            max_tokens,
+            retry_ratio,
+        }
+    }
+}
+
+impl Default for RetryBudget {
+    fn default() -> Self {
+        Self::new(100, 0.2)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RetryPolicy {
+    max_retries: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    budget: Option<BudgetState>,
+}
+
+#[derive(Debug)]
+struct BudgetState {
+    config: RetryBudget,
+    tokens: Mutex<f64>,
+}
+
+impl RetryPolicy {
+    pub(crate) fn new(
+        max_retries: u32,
+        base_delay: Duration,
+        max_delay: Duration,
+        budget: Option<RetryBudget>,
+    ) -> Self {
+        Self {
+            max_retries,
+            base_delay,
+            max_delay,
+            budget: budget.map(|config| BudgetState {
+                config,
+                tokens: Mutex::new(config.max_tokens as f64),
+            }),
+        }
+    }
+
+    pub(crate) fn should_retry(&self, attempt: u32) -> bool {
+        if attempt >= self.max_retries {
+            return false;
+        }
+
+        match &self.budget {
+            Some(budget) => {
+                let mut tokens = budget.tokens.lock().unwrap();
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    true
+                } else {
+                    false
+                }
+            }
+            None => true,
+        }
+    }
+
+    pub(crate) fn record_success(&self) {
+        if let Some(budget) = &self.budget {
+            let mut tokens = budget.tokens.lock().unwrap();
+            *tokens = (*tokens + budget.config.retry_ratio).min(budget.config.max_tokens as f64);
+        }
+    }
+
+    /// Exponential backoff with "equal jitter": half of the delay is fixed, the other half random.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = exp / 2;
+        half + half.mul_f64(random_fraction())
+    }
+}
+
+pub(crate) fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE
+    )
+}
+
+pub(crate) const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// A random version 4 UUID identifying one logical call across its retries.
+pub(crate) fn idempotency_key() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut bytes = [0u8; 16];
+    for (index, chunk) in bytes.chunks_mut(8).enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        hasher.write_usize(index);
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default(),
+        );
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+pub(crate) fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+pub(crate) fn is_retryable_error(error: &NodestyError) -> bool {
+    match error {
+        NodestyError::Timeout => true,
+        NodestyError::Transport(error) => error.is_connect() || error.is_timeout(),
+        _ => false,
+    }
+}
+
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::TransportError;
+
+    #[test]
+    fn retries_stop_at_the_limit() {
+        let policy = RetryPolicy::new(2, Duration::ZERO, Duration::ZERO, None);
+        assert!(policy.should_retry(0));
+        assert!(policy.should_retry(1));
+        assert!(!policy.should_retry(2));
+    }
+
+    #[test]
+    fn budget_is_spent_and_refilled() {
+        let policy = RetryPolicy::new(5, Duration::ZERO, Duration::ZERO, Some(RetryBudget::new(1, 0.5)));
+        assert!(policy.should_retry(0));
+        assert!(!policy.should_retry(0));
+
+        policy.record_success();
+        assert!(!policy.should_retry(0));
+        policy.record_success();
+        assert!(policy.should_retry(0));
+    }
+
+    #[test]
+    fn backoff_grows_up_to_the_maximum() {
+        let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_secs(1), None);
+        for _ in 0..20 {
+            let first = policy.backoff(0);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let capped = policy.backoff(10);
+            assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn idempotency_keys_are_unique_v4_uuids() {
+        let key = idempotency_key();
+        assert_eq!(key.len(), 36);
+        assert_eq!(key.as_bytes()[14], b'4');
+        assert!(matches!(key.as_bytes()[19], b'8' | b'9' | b'a' | b'b'));
+        assert_ne!(key, idempotency_key());
+    }
+
+    #[test]
+    fn only_transient_failures_are_retryable() {
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+
+        assert!(is_retryable_error(&NodestyError::Timeout));
+        assert!(is_retryable_error(&NodestyError::Transport(TransportError::connect("refused"))));
+        assert!(!is_retryable_error(&NodestyError::Transport(TransportError::other("bad body"))));
+        assert!(!is_retryable_error(&NodestyError::Cancelled));
+
+        assert!(is_idempotent(&Method::PUT));
+        assert!(!is_idempotent(&Method::POST));
+    }
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+/// An API path together with the template it was built from.
+///
+/// Services build routes such as `Route::new("VpsApiService::get_details", "/services/{id}/vps/info")
+/// .param("id", id)`, so instrumentation can group calls by `template` instead of by raw IDs.
+/// The `{id}` parameter is reported as the service ID. Plain strings convert into routes whose
+/// template is the path itself.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Route {
+    operation: Option<&'static str>,
+    template: String,
+    path: String,
+    service_id: Option<String>,
+}
+
+impl Route {
+    pub fn new(operation: &'static str, template: &'static str) -> Self {
+        Self {
+            operation: Some(operation),
+            template: template.to_string(),
+            path: template.to_string(),
+            service_id: None,
+        }
+    }
+
+    /// Substitutes `{name}` in the path with `value`.
+    pub fn param(mut self, name: &str, value: impl fmt::Display) -> Self {
+        let value = value.to_string();
+        self.path = self.path.replace(&format!("{{{}}}", name), &value);
+        if name == "id" {
+            self.service_id = Some(value);
+        }
+        self
+    }
+
+    /// Appends `name=value` to the query string, percent-encoding both.
+    pub fn query(mut self, name: &str, value: impl fmt::Display) -> Self {
+        let separator = if self.path.contains('?') { '&' } else { '?' };
+        self.path.push(separator);
+        encode_query_component(&mut self.path, name);
+        self.path.push('=');
+        encode_query_component(&mut self.path, &value.to_string());
+        self
+    }
+
+    /// The service method that issued the request, for example `VpsApiService::get_details`.
+    pub fn operation(&self) -> Option<&'static str> {
+        self.operation
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn service_id(&self) -> Option<&str> {
+        self.service_id.as_deref()
+    }
+}
+
+fn encode_query_component(out: &mut String, component: &str) {
+    for byte in component.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            byte => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+}
+
+impl From<&str> for Route {
+    fn from(path: &str) -> Self {
+        Self {
+            operation: None,
+            template: path.to_string(),
+            path: path.to_string(),
+            service_id: None,
+        }
+    }
+}
+
+impl From<&String> for Route {
+    fn from(path: &String) -> Self {
+        Self::from(path.as_str())
+    }
+}
+
+impl From<String> for Route {
+    fn from(path: String) -> Self {
+        Self::from(path.as_str())
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
@@ -1,19 +1,17 @@
-use crate::models::{
-    dedicated::{
-        DedicatedServerAction,
-        DedicatedServerDetails,
-        DedicatedServerHardwareComponent,
-        DedicatedServerOsTemplate,
-        DedicatedServerReinstallData,
-        DedicatedServerReinstallStatus,
-        DedicatedServerTask,
-    },
-    ApiResponse,
+use crate::models::dedicated::{
+    DedicatedServerAction,
+    DedicatedServerDetails,
+    DedicatedServerHardwareComponent,
+    DedicatedServerOsTemplate,
+    DedicatedServerReinstallData,
+    DedicatedServerReinstallStatus,
+    DedicatedServerTask,
 };
-use crate::NodestyApiClient;
-use reqwest::{Error, Method};
+use crate::{ApiCall, NodestyApiClient, Route};
+use reqwest::Method;
 use std::sync::Arc;
 
+#[derive(Debug, Clone)]
 pub struct DedicatedServerApiService {
     client: Arc<NodestyApiClient>,
 }
@@ -25,56 +23,61 @@ impl DedicatedServerApiService {
         }
     }
 
-    pub async fn perform_action(
+    #[cfg(feature = "firewall")]
+    pub(crate) fn client(&self) -> Arc<NodestyApiClient> {
+        self.client.clone()
+    }
+
+    pub fn perform_action(
         &self,
         id: &str,
         action: DedicatedServerAction,
-    ) -> Result<ApiResponse<()>, Error> {
+    ) -> ApiCall<'_, ()> {
         let body = serde_json::json!({ "action": action });
-        self.client.send_request(Method::POST, &format!("/services/{}/dedicated/action", id), Some(body)).await
+        self.client.request(Method::POST, Route::new("DedicatedServerApiService::perform_action", "/services/{id}/dedicated/action").param("id", id), Some(body))
     }
 
-    pub async fn get_details(
+    pub fn get_details(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<DedicatedServerDetails>, Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/dedicated/info", id), None).await
+    ) -> ApiCall<'_, DedicatedServerDetails> {
+        self.client.request(Method::GET, Route::new("DedicatedServerApiService::get_details", "/services/{id}/dedicated/info").param("id", id), None)
     }
 
-    pub async fn get_hardware_components(
+    pub fn get_hardware_components(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<Vec<DedicatedServerHardwareComponent>>, Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/dedicated/hardware", id), None).await
+    ) -> ApiCall<'_, Vec<DedicatedServerHardwareComponent>> {
+        self.client.request(Method::GET, Route::new("DedicatedServerApiService::get_hardware_components", "/services/{id}/dedicated/hardware").param("id", id), None)
     }
 
-    pub async fn get_os_templates(
+    pub fn get_os_templates(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<Vec<DedicatedServerOsTemplate>>, Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/dedicated/os-templates", id), None).await
+    ) -> ApiCall<'_, Vec<DedicatedServerOsTemplate>> {
+        self.client.request(Method::GET, Route::new("DedicatedServerApiService::get_os_templates", "/services/{id}/dedicated/os-templates").param("id", id), None)
     }
 
-    pub async fn get_reinstall_status(
+    pub fn get_reinstall_status(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<DedicatedServerReinstallStatus>, Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/dedicated/reinstall-status", id), None).await
+    ) -> ApiCall<'_, DedicatedServerReinstallStatus> {
+        self.client.request(Method::GET, Route::new("DedicatedServerApiService::get_reinstall_status", "/services/{id}/dedicated/reinstall-status").param("id", id), None)
     }
 
-    pub async fn reinstall(
+    pub fn reinstall(
         &self,
         id: &str,
         data: DedicatedServerReinstallData,
-    ) -> Result<ApiResponse<()>, Error> {
+    ) -> ApiCall<'_, ()> {
         let body = serde_json::to_value(&data).ok();
-        self.client.send_request(Method::POST, &format!("/services/{}/dedicated/reinstall", id), body).await
+        self.client.request(Method::POST, Route::new("DedicatedServerApiService::reinstall", "/services/{id}/dedicated/reinstall").param("id", id), body)
     }
 
-    pub async fn get_tasks(
+    pub fn get_tasks(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<Vec<DedicatedServerTask>>, Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/dedicated/tasks", id), None).await
+    ) -> ApiCall<'_, Vec<DedicatedServerTask>> {
+        self.client.request(Method::GET, Route::new("DedicatedServerApiService::get_tasks", "/services/{id}/dedicated/tasks").param("id", id), None)
     }
 }
\ No newline at end of file
@@ -1,14 +1,12 @@
-use crate::models::{
-    firewall::{
-        AttackNotificationSettings, FirewallAttackLog, FirewallCreateRuleData, FirewallReverseDns,
-        FirewallRule, FirewallStatistics,
-    },
-    ApiResponse,
+use crate::models::firewall::{
+    AttackNotificationSettings, FirewallAttackLog, FirewallCreateRuleData, FirewallReverseDns,
+    FirewallRule, FirewallStatistics,
 };
-use crate::NodestyApiClient;
-use reqwest::{Error, Method};
+use crate::{ApiCall, NodestyApiClient, Route};
+use reqwest::Method;
 use std::sync::Arc;
 
+#[derive(Debug, Clone)]
 pub struct FirewallApiService {
     client: Arc<NodestyApiClient>,
 }
@@ -18,156 +16,191 @@ impl FirewallApiService {
         Self { client }
     }
 
-    pub async fn get_attack_logs(
+    pub fn get_attack_logs(
         &self,
         service_id: &str,
         ip: &str,
-    ) -> Result<ApiResponse<Vec<FirewallAttackLog>>, Error> {
+    ) -> ApiCall<'_, Vec<FirewallAttackLog>> {
         self.client
-            .send_request(
+            .request(
                 Method::GET,
-                &format!("/services/{}/firewall/{}/attack-logs", service_id, ip),
+                Route::new(
+                    "FirewallApiService::get_attack_logs",
+                    "/services/{id}/firewall/{ip}/attack-logs",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 None,
             )
-            .await
     }
 
-    pub async fn get_attack_notification_settings(
+    pub fn get_attack_notification_settings(
         &self,
         service_id: &str,
         ip: &str,
-    ) -> Result<ApiResponse<AttackNotificationSettings>, Error> {
+    ) -> ApiCall<'_, AttackNotificationSettings> {
         self.client
-            .send_request(
+            .request(
                 Method::GET,
-                &format!(
-                    "/services/{}/firewall/{}/attack-notification",
-                    service_id, ip
-                ),
+                Route::new(
+                    "FirewallApiService::get_attack_notification_settings",
+                    "/services/{id}/firewall/{ip}/attack-notification",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 None,
             )
-            .await
     }
 
-    pub async fn update_attack_notification_settings(
+    pub fn update_attack_notification_settings(
         &self,
         service_id: &str,
         ip: &str,
         data: AttackNotificationSettings,
-    ) -> Result<ApiResponse<AttackNotificationSettings>, Error> {
+    ) -> ApiCall<'_, AttackNotificationSettings> {
         let body = serde_json::to_value(&data).ok();
         self.client
-            .send_request(
+            .request(
                 Method::PUT,
-                &format!(
-                    "/services/{}/firewall/{}/attack-notification",
-                    service_id, ip
-                ),
+                Route::new(
+                    "FirewallApiService::update_attack_notification_settings",
+                    "/services/{id}/firewall/{ip}/attack-notification",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 body,
             )
-            .await
     }
 
-    pub async fn reset_reverse_dns(
+    pub fn reset_reverse_dns(
         &self,
         service_id: &str,
         ip: &str,
-    ) -> Result<ApiResponse<()>, Error> {
+    ) -> ApiCall<'_, ()> {
         self.client
-            .send_request(
+            .request(
                 Method::DELETE,
-                &format!("/services/{}/firewall/{}/rdns", service_id, ip),
+                Route::new(
+                    "FirewallApiService::reset_reverse_dns",
+                    "/services/{id}/firewall/{ip}/rdns",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 None,
             )
-            .await
     }
 
-    pub async fn get_reverse_dns(
+    pub fn get_reverse_dns(
         &self,
         service_id: &str,
         ip: &str,
-    ) -> Result<ApiResponse<FirewallReverseDns>, Error> {
+    ) -> ApiCall<'_, FirewallReverseDns> {
         self.client
-            .send_request(
+            .request(
                 Method::GET,
-                &format!("/services/{}/firewall/{}/rdns", service_id, ip),
+                Route::new(
+                    "FirewallApiService::get_reverse_dns",
+                    "/services/{id}/firewall/{ip}/rdns",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 None,
             )
-            .await
     }
 
-    pub async fn upsert_reverse_dns(
+    pub fn upsert_reverse_dns(
         &self,
         service_id: &str,
         ip: &str,
         data: FirewallReverseDns,
-    ) -> Result<ApiResponse<()>, Error> {
+    ) -> ApiCall<'_, ()> {
         let body = serde_json::to_value(&data).ok();
         self.client
-            .send_request(
+            .request(
                 Method::PUT,
-                &format!("/services/{}/firewall/{}/rdns", service_id, ip),
+                Route::new(
+                    "FirewallApiService::upsert_reverse_dns",
+                    "/services/{id}/firewall/{ip}/rdns",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 body,
             )
-            .await
     }
 
-    pub async fn delete_rule(
+    pub fn delete_rule(
         &self,
         service_id: &str,
         ip: &str,
         rule_id: u32,
-    ) -> Result<ApiResponse<()>, Error> {
+    ) -> ApiCall<'_, ()> {
         self.client
-            .send_request(
+            .request(
                 Method::DELETE,
-                &format!("/services/{}/firewall/{}/rules/{}", service_id, ip, rule_id),
+                Route::new(
+                    "FirewallApiService::delete_rule",
+                    "/services/{id}/firewall/{ip}/rules/{rule_id}",
+                )
+                .param("id", service_id)
+                .param("ip", ip)
+                .param("rule_id", rule_id),
                 None,
             )
-            .await
     }
 
-    pub async fn get_rules(
+    pub fn get_rules(
         &self,
         service_id: &str,
         ip: &str,
-    ) -> Result<ApiResponse<Vec<FirewallRule>>, Error> {
+    ) -> ApiCall<'_, Vec<FirewallRule>> {
         self.client
-            .send_request(
+            .request(
                 Method::GET,
-                &format!("/services/{}/firewall/{}/rules", service_id, ip),
+                Route::new(
+                    "FirewallApiService::get_rules",
+                    "/services/{id}/firewall/{ip}/rules",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 None,
             )
-            .await
     }
 
-    pub async fn create_rule(
+    pub fn create_rule(
         &self,
         service_id: &str,
         ip: &str,
         data: FirewallCreateRuleData,
-    ) -> Result<ApiResponse<()>, Error> {
+    ) -> ApiCall<'_, ()> {
         let body = serde_json::to_value(&data).ok();
         self.client
-            .send_request(
+            .request(
                 Method::POST,
-                &format!("/services/{}/firewall/{}/rules", service_id, ip),
+                Route::new(
+                    "FirewallApiService::create_rule",
+                    "/services/{id}/firewall/{ip}/rules",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 body,
             )
-            .await
     }
 
-    pub async fn get_statistics(
+    pub fn get_statistics(
         &self,
         service_id: &str,
         ip: &str,
-    ) -> Result<ApiResponse<Vec<FirewallStatistics>>, Error> {
+    ) -> ApiCall<'_, Vec<FirewallStatistics>> {
         self.client
-            .send_request(
+            .request(
                 Method::GET,
-                &format!("/services/{}/firewall/{}/stats", service_id, ip),
+                Route::new(
+                    "FirewallApiService::get_statistics",
+                    "/services/{id}/firewall/{ip}/stats",
+                )
+                .param("id", service_id)
+                .param("ip", ip),
                 None,
             )
-            .await
     }
 }
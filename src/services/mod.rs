@@ -1,9 +1,17 @@
+#[cfg(feature = "dedicated")]
 pub mod dedicated;
+#[cfg(feature = "firewall")]
 pub mod firewall;
+#[cfg(feature = "user")]
 pub mod user;
+#[cfg(feature = "vps")]
 pub mod vps;
 
+#[cfg(feature = "dedicated")]
 pub use dedicated::DedicatedServerApiService;
+#[cfg(feature = "firewall")]
 pub use firewall::FirewallApiService;
+#[cfg(feature = "user")]
 pub use user::UserApiService;
+#[cfg(feature = "vps")]
 pub use vps::VpsApiService;
@@ -1,21 +1,19 @@
-use reqwest::{Error, Method};
+use reqwest::Method;
 use std::sync::Arc;
 
-use crate::models::{
-    user::{
-        Invoice,
-        Service,
-        Session,
-        Ticket,
-        User,
-        UserInvoiceSummary,
-        UserTicketSummary,
-    },
-    ApiResponse,
+use crate::models::user::{
+    Invoice,
+    Service,
+    Session,
+    Ticket,
+    User,
+    UserInvoiceSummary,
+    UserTicketSummary,
 };
 
-use crate::NodestyApiClient;
+use crate::{ApiCall, NodestyApiClient, Route};
 
+#[derive(Debug, Clone)]
 pub struct UserApiService {
     client: Arc<NodestyApiClient>,
 }
@@ -26,37 +24,37 @@ impl UserApiService {
             client
         }
     }
-    pub async fn get_services(&self) -> Result<ApiResponse<Vec<Service>>, Error> {
-        self.client.send_request(Method::GET, "/services", None).await
+    pub fn get_services(&self) -> ApiCall<'_, Vec<Service>> {
+        self.client.request(Method::GET, Route::new("UserApiService::get_services", "/services"), None)
     }
 
-    pub async fn get_ticket_by_id(
+    pub fn get_ticket_by_id(
         &self,
         ticket_id: &str,
-    ) -> Result<ApiResponse<Ticket>, Error> {
-        self.client.send_request(Method::GET, &format!("/tickets/{}", ticket_id), None).await
+    ) -> ApiCall<'_, Ticket> {
+        self.client.request(Method::GET, Route::new("UserApiService::get_ticket_by_id", "/tickets/{ticket_id}").param("ticket_id", ticket_id), None)
     }
 
-    pub async fn get_tickets(&self) -> Result<ApiResponse<Vec<UserTicketSummary>>, Error> {
-        self.client.send_request(Method::GET, "/tickets", None).await
+    pub fn get_tickets(&self) -> ApiCall<'_, Vec<UserTicketSummary>> {
+        self.client.request(Method::GET, Route::new("UserApiService::get_tickets", "/tickets"), None)
     }
 
-    pub async fn get_current_user(&self) -> Result<ApiResponse<User>, Error> {
-        self.client.send_request(Method::GET, "/users/@me", None).await
+    pub fn get_current_user(&self) -> ApiCall<'_, User> {
+        self.client.request(Method::GET, Route::new("UserApiService::get_current_user", "/users/@me"), None)
     }
 
-    pub async fn get_invoice_by_id(
+    pub fn get_invoice_by_id(
         &self,
         invoice_id: &str,
-    ) -> Result<ApiResponse<Invoice>, Error> {
-        self.client.send_request(Method::GET, &format!("/users/@me/invoices/{}", invoice_id), None).await
+    ) -> ApiCall<'_, Invoice> {
+        self.client.request(Method::GET, Route::new("UserApiService::get_invoice_by_id", "/users/@me/invoices/{invoice_id}").param("invoice_id", invoice_id), None)
     }
 
-    pub async fn get_invoices(&self) -> Result<ApiResponse<Vec<UserInvoiceSummary>>, Error> {
-        self.client.send_request(Method::GET, "/users/@me/invoices", None).await
+    pub fn get_invoices(&self) -> ApiCall<'_, Vec<UserInvoiceSummary>> {
+        self.client.request(Method::GET, Route::new("UserApiService::get_invoices", "/users/@me/invoices"), None)
     }
 
-    pub async fn get_sessions(&self) -> Result<ApiResponse<Vec<Session>>, Error> {
-        self.client.send_request(Method::GET, "/users/@me/sessions", None).await
+    pub fn get_sessions(&self) -> ApiCall<'_, Vec<Session>> {
+        self.client.request(Method::GET, Route::new("UserApiService::get_sessions", "/users/@me/sessions"), None)
     }
 }
\ No newline at end of file
@@ -1,20 +1,18 @@
-use crate::models::{
-    vps::{
-        VpsAction,
-        VpsBackup,
-        VpsChangePasswordData,
-        VpsDetails,
-        VpsGraphs,
-        VpsOsTemplate,
-        VpsReinstallData,
-        VpsTask,
-    },
-    ApiResponse,
+use crate::models::vps::{
+    VpsAction,
+    VpsBackup,
+    VpsChangePasswordData,
+    VpsDetails,
+    VpsGraphs,
+    VpsOsTemplate,
+    VpsReinstallData,
+    VpsTask,
 };
-use crate::NodestyApiClient;
+use crate::{ApiCall, NodestyApiClient, Route};
 use reqwest::Method;
 use std::sync::Arc;
 
+#[derive(Debug, Clone)]
 pub struct VpsApiService {
     client: Arc<NodestyApiClient>,
 }
@@ -26,73 +24,78 @@ impl VpsApiService {
         }
     }
 
-    pub async fn perform_action(
+    #[cfg(feature = "firewall")]
+    pub(crate) fn client(&self) -> Arc<NodestyApiClient> {
+        self.client.clone()
+    }
+
+    pub fn perform_action(
         &self,
         id: &str,
         action: VpsAction,
-    ) -> Result<ApiResponse<()>, reqwest::Error> {
+    ) -> ApiCall<'_, ()> {
         let body = serde_json::json!({ "action": action });
-        self.client.send_request(Method::POST, &format!("/services/{}/vps/action", id), Some(body)).await
+        self.client.request(Method::POST, Route::new("VpsApiService::perform_action", "/services/{id}/vps/action").param("id", id), Some(body))
     }
 
-    pub async fn restore_backup(
+    pub fn restore_backup(
         &self,
         id: &str,
         data: &VpsBackup,
-    ) -> Result<ApiResponse<()>, reqwest::Error> {
-        self.client.send_request(Method::POST, &format!("/services/{}/vps/backups/{}/{}", id, data.date, data.file), None).await
+    ) -> ApiCall<'_, ()> {
+        self.client.request(Method::POST, Route::new("VpsApiService::restore_backup", "/services/{id}/vps/backups/{date}/{file}").param("id", id).param("date", &data.date).param("file", &data.file), None)
     }
 
-    pub async fn get_backups(
+    pub fn get_backups(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<Vec<VpsBackup>>, reqwest::Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/vps/backups", id), None).await
+    ) -> ApiCall<'_, Vec<VpsBackup>> {
+        self.client.request(Method::GET, Route::new("VpsApiService::get_backups", "/services/{id}/vps/backups").param("id", id), None)
     }
 
-    pub async fn change_password(
+    pub fn change_password(
         &self,
         id: &str,
         data: VpsChangePasswordData,
-    ) -> Result<ApiResponse<()>, reqwest::Error> {
+    ) -> ApiCall<'_, ()> {
         let body = serde_json::to_value(&data).ok();
-        self.client.send_request(Method::POST, &format!("/services/{}/vps/change-password", id), body).await
+        self.client.request(Method::POST, Route::new("VpsApiService::change_password", "/services/{id}/vps/change-password").param("id", id), body)
     }
 
-    pub async fn get_usage_statistics(
+    pub fn get_usage_statistics(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<VpsGraphs>, reqwest::Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/vps/graphs", id), None).await
+    ) -> ApiCall<'_, VpsGraphs> {
+        self.client.request(Method::GET, Route::new("VpsApiService::get_usage_statistics", "/services/{id}/vps/graphs").param("id", id), None)
     }
 
-    pub async fn get_details(
+    pub fn get_details(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<VpsDetails>, reqwest::Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/vps/info", id), None).await
+    ) -> ApiCall<'_, VpsDetails> {
+        self.client.request(Method::GET, Route::new("VpsApiService::get_details", "/services/{id}/vps/info").param("id", id), None)
     }
 
-    pub async fn get_os_templates(
+    pub fn get_os_templates(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<Vec<VpsOsTemplate>>, reqwest::Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/vps/os-templates", id), None).await
+    ) -> ApiCall<'_, Vec<VpsOsTemplate>> {
+        self.client.request(Method::GET, Route::new("VpsApiService::get_os_templates", "/services/{id}/vps/os-templates").param("id", id), None)
     }
 
-    pub async fn reinstall(
+    pub fn reinstall(
         &self,
         id: &str,
         data: VpsReinstallData,
-    ) -> Result<ApiResponse<()>, reqwest::Error> {
+    ) -> ApiCall<'_, ()> {
         let body = serde_json::to_value(&data).ok();
-        self.client.send_request(Method::POST, &format!("/services/{}/vps/reinstall", id), body).await
+        self.client.request(Method::POST, Route::new("VpsApiService::reinstall", "/services/{id}/vps/reinstall").param("id", id), body)
     }
 
-    pub async fn get_tasks(
+    pub fn get_tasks(
         &self,
         id: &str,
-    ) -> Result<ApiResponse<Vec<VpsTask>>, reqwest::Error> {
-        self.client.send_request(Method::GET, &format!("/services/{}/vps/tasks", id), None).await
+    ) -> ApiCall<'_, Vec<VpsTask>> {
+        self.client.request(Method::GET, Route::new("VpsApiService::get_tasks", "/services/{id}/vps/tasks").param("id", id), None)
     }
 }
\ No newline at end of file
@@ -0,0 +1,156 @@
+use crate::transport::{HttpRequest, HttpResponse};
+use crate::NodestyError;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+
+type Waiter = oneshot::Sender<Result<HttpResponse, NodestyError>>;
+
+/// Lets concurrent identical GETs share one request. The first caller becomes the leader and
+/// sends the request; callers arriving while it is in flight wait for its response.
+#[derive(Debug, Default)]
+pub(crate) struct SingleFlight {
+    calls: Mutex<HashMap<String, Vec<Waiter>>>,
+}
+
+pub(crate) enum Flight<'a> {
+    Leader(FlightGuard<'a>),
+    Follower(oneshot::Receiver<Result<HttpResponse, NodestyError>>),
+}
+
+impl SingleFlight {
+    pub(crate) fn join(&self, key: String) -> Flight<'_> {
+        let mut calls = self.calls.lock().unwrap();
+        match calls.get_mut(&key) {
+            Some(waiters) => {
+                let (sender, receiver) = oneshot::channel();
+                waiters.push(sender);
+                Flight::Follower(receiver)
+            }
+            None => {
+                calls.insert(key.clone(), Vec::new());
+                Flight::Leader(FlightGuard {
+                    flights: self,
+                    key,
+                    completed: false,
+                })
+            }
+        }
+    }
+}
+
+/// Held by the leader. Dropping it without calling [`complete`](Self::complete), for example
+/// because the leader's future was cancelled, releases the waiters so they send their own request.
+pub(crate) struct FlightGuard<'a> {
+    flights: &'a SingleFlight,
+    key: String,
+    completed: bool,
+}
+
+impl FlightGuard<'_> {
+    pub(crate) fn complete(mut self, result: &Result<HttpResponse, NodestyError>) {
+        self.completed = true;
+        let waiters = self.flights.calls.lock().unwrap().remove(&self.key).unwrap_or_default();
+        for waiter in waiters {
+            let shared = match result {
+                Ok(response) => Ok(response.clone()),
+                Err(error) => Err(error.duplicate()),
+            };
+            let _ = waiter.send(shared);
+        }
+    }
+}
+
+impl Drop for FlightGuard<'_> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.flights.calls.lock().unwrap().remove(&self.key);
+        }
+    }
+}
+
+/// Requests only share a flight if they would be sent identically, including per-call
+/// headers, query parameters and timeout.
+pub(crate) fn key(request: &HttpRequest) -> String {
+    format!("{} {} {:?} {:?}", request.method, request.url, request.headers, request.timeout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+    use reqwest::StatusCode;
+
+    fn response() -> HttpResponse {
+        HttpResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: b"[]".to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn followers_share_the_leaders_result() {
+        let flights = SingleFlight::default();
+        let Flight::Leader(guard) = flights.join("GET /a".to_string()) else {
+            panic!("the first caller leads");
+        };
+        let Flight::Follower(first) = flights.join("GET /a".to_string()) else {
+            panic!("later callers follow");
+        };
+        let Flight::Follower(second) = flights.join("GET /a".to_string()) else {
+            panic!("later callers follow");
+        };
+        assert!(matches!(flights.join("GET /b".to_string()), Flight::Leader(_)));
+
+        guard.complete(&Ok(response()));
+        assert_eq!(first.await.unwrap().unwrap().body, b"[]");
+        assert_eq!(second.await.unwrap().unwrap().body, b"[]");
+        assert!(matches!(flights.join("GET /a".to_string()), Flight::Leader(_)));
+    }
+
+    #[tokio::test]
+    async fn errors_are_copied_to_every_follower() {
+        let flights = SingleFlight::default();
+        let Flight::Leader(guard) = flights.join("GET /a".to_string()) else {
+            panic!("the first caller leads");
+        };
+        let Flight::Follower(follower) = flights.join("GET /a".to_string()) else {
+            panic!("later callers follow");
+        };
+
+        guard.complete(&Err(NodestyError::Timeout));
+        assert!(matches!(follower.await.unwrap(), Err(NodestyError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn cancelled_leader_releases_its_followers() {
+        let flights = SingleFlight::default();
+        let Flight::Leader(guard) = flights.join("GET /a".to_string()) else {
+            panic!("the first caller leads");
+        };
+        let Flight::Follower(follower) = flights.join("GET /a".to_string()) else {
+            panic!("later callers follow");
+        };
+
+        drop(guard);
+        assert!(follower.await.is_err());
+        assert!(matches!(flights.join("GET /a".to_string()), Flight::Leader(_)));
+    }
+
+    #[test]
+    fn key_includes_headers_and_timeout() {
+        let request = HttpRequest {
+            method: reqwest::Method::GET,
+            url: "https://nodesty.com/api/services".to_string(),
+            headers: HeaderMap::new(),
+            body: None,
+            timeout: None,
+        };
+        let mut with_timeout = request.clone();
+        with_timeout.timeout = Some(std::time::Duration::from_secs(1));
+
+        assert_eq!(key(&request), key(&request.clone()));
+        assert_ne!(key(&request), key(&with_timeout));
+    }
+}
@@ -0,0 +1,208 @@
+use crate::circuit_breaker::CircuitEvent;
+use crate::concurrency::Priority;
+#[cfg(feature = "metrics")]
+use crate::metrics::ClientMetrics;
+use crate::transport::HttpResponse;
+use crate::{NodestyApiClient, NodestyError, Route};
+use reqwest::Method;
+use std::future::Future;
+use std::time::Duration;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use std::time::Instant;
+
+/// Instrumentation for one logical API call, across all of its attempts.
+/// Every hook is a no-op unless the `tracing` or `metrics` feature is enabled.
+pub(crate) struct RequestTelemetry<'a> {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+    #[cfg(feature = "metrics")]
+    metrics: &'a ClientMetrics,
+    #[cfg(feature = "metrics")]
+    method: Method,
+    #[cfg(feature = "metrics")]
+    endpoint: String,
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    started: Instant,
+    #[cfg(not(feature = "metrics"))]
+    _marker: std::marker::PhantomData<&'a ()>,
+}
+
+// Each feature only reads some of the hook arguments.
+#[cfg_attr(
+    not(all(feature = "tracing", feature = "metrics")),
+    allow(unused_variables)
+)]
+impl<'a> RequestTelemetry<'a> {
+    pub(crate) fn start(client: &'a NodestyApiClient, method: &Method, route: &Route) -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            span: tracing::info_span!(
+                "nodesty.request",
+                otel.name = route.operation().unwrap_or(route.template()),
+                operation = route.operation(),
+                http.method = %method,
+                path = route.template(),
+                service_id = route.service_id(),
+                status = tracing::field::Empty,
+                attempt = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+            ),
+            #[cfg(feature = "metrics")]
+            metrics: client.metrics(),
+            #[cfg(feature = "metrics")]
+            method: method.clone(),
+            // Routes built from a plain path have their IDs baked into the template.
+            #[cfg(feature = "metrics")]
+            endpoint: match route.operation() {
+                Some(_) => route.template().to_string(),
+                None => "other".to_string(),
+            },
+            #[cfg(any(feature = "tracing", feature = "metrics"))]
+            started: Instant::now(),
+            #[cfg(not(feature = "metrics"))]
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        tracing::Instrument::instrument(future, self.span.clone())
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> F {
+        future
+    }
+
+    pub(crate) fn rate_limited(&self, waited: Duration) {
+        #[cfg(feature = "tracing")]
+        if !waited.is_zero() {
+            tracing::debug!(
+                parent: &self.span,
+                waited_ms = waited.as_millis() as u64,
+                "held back by rate limiter"
+            );
+        }
+
+        #[cfg(feature = "metrics")]
+        if !waited.is_zero() {
+            self.metrics.observe_rate_limit_wait(waited.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn queued(&self, priority: Priority, waited: Duration) {
+        #[cfg(feature = "tracing")]
+        if !waited.is_zero() {
+            tracing::debug!(
+                parent: &self.span,
+                priority = priority.as_str(),
+                waited_ms = waited.as_millis() as u64,
+                "queued for a concurrency slot"
+            );
+        }
+
+        #[cfg(feature = "metrics")]
+        if !waited.is_zero() {
+            self.metrics.observe_queue_wait(priority.as_str(), waited.as_secs_f64());
+        }
+    }
+
+    pub(crate) fn attempt(&self, attempt: u32, result: &Result<HttpResponse, NodestyError>) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("attempt", attempt);
+            if let Ok(response) = result {
+                self.span.record("status", response.status.as_u16());
+            }
+        }
+    }
+
+    pub(crate) fn retrying(
+        &self,
+        attempt: u32,
+        result: &Result<HttpResponse, NodestyError>,
+        delay: Duration,
+    ) {
+        #[cfg(feature = "tracing")]
+        match result {
+            Ok(response) => tracing::warn!(
+                parent: &self.span,
+                attempt,
+                status = response.status.as_u16(),
+                delay_ms = delay.as_millis() as u64,
+                "retrying nodesty request"
+            ),
+            Err(error) => tracing::warn!(
+                parent: &self.span,
+                attempt,
+                error.kind = error.kind(),
+                error.message = %error,
+                delay_ms = delay.as_millis() as u64,
+                "retrying nodesty request"
+            ),
+        }
+
+        #[cfg(feature = "metrics")]
+        self.metrics
+            .observe_retry(self.method.as_str(), &self.endpoint);
+    }
+
+    pub(crate) fn refresh_failed(&self, error: &NodestyError) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            parent: &self.span,
+            error.kind = error.kind(),
+            error.message = %error,
+            "failed to refresh nodesty access token"
+        );
+    }
+
+    pub(crate) fn audit_failed(&self, error: &std::io::Error) {
+        #[cfg(feature = "tracing")]
+        tracing::error!(
+            parent: &self.span,
+            error.message = %error,
+            "failed to write nodesty audit record"
+        );
+    }
+
+    /// `status` is the HTTP status of the final response, if one was received.
+    pub(crate) fn finish<T>(&self, result: &Result<T, NodestyError>, status: Option<u16>) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span
+                .record("latency_ms", self.started.elapsed().as_millis() as u64);
+
+            if let Err(error) = result {
+                tracing::warn!(
+                    parent: &self.span,
+                    error.kind = error.kind(),
+                    error.status = error.status().map(|status| status.as_u16()),
+                    error.message = %error,
+                    "nodesty request failed"
+                );
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        self.metrics.observe_request(
+            self.method.as_str(),
+            &self.endpoint,
+            status,
+            result.as_ref().err().map(NodestyError::kind),
+            self.started.elapsed().as_secs_f64(),
+        );
+    }
+}
+
+/// Logs a circuit breaker state change. A no-op unless the `tracing` feature is enabled.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn circuit_changed(event: &CircuitEvent) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        group = %event.group,
+        from = event.from.as_str(),
+        to = event.to.as_str(),
+        "nodesty circuit breaker changed state"
+    );
+}
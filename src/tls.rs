@@ -0,0 +1,201 @@
+use crate::models::ClientIdentity;
+use crate::NodestyError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use std::sync::Arc;
+
+/// Builds the rustls configuration used when SPKI pins are configured. The pins are checked
+/// by the certificate verifier, so a mismatch aborts the handshake before any request is sent.
+pub(crate) fn pinned_config(
+    root_certificates: &[Vec<u8>],
+    client_identity: Option<&ClientIdentity>,
+    pins: &[String],
+) -> Result<ClientConfig, NodestyError> {
+    let pins = pins
+        .iter()
+        .map(|pin| decode_pin(pin))
+        .collect::<Result<Vec<_>, _>>()?;
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let mut roots = RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    for pem in root_certificates {
+        for certificate in CertificateDer::pem_slice_iter(pem) {
+            let certificate = certificate
+                .map_err(|error| NodestyError::config("root_certificates", error.to_string()))?;
+            roots
+                .add(certificate)
+                .map_err(|error| NodestyError::config("root_certificates", error.to_string()))?;
+        }
+    }
+
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .map_err(|error| NodestyError::config("root_certificates", error.to_string()))?;
+
+    let builder = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|error| NodestyError::config("pinned_spki_sha256", error.to_string()))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PinnedVerifier { inner, pins }));
+
+    let mut config = match client_identity {
+        None => builder.with_no_client_auth(),
+        Some(ClientIdentity::Pem {
+            certificate_chain,
+            private_key,
+        }) => {
+            let chain = CertificateDer::pem_slice_iter(certificate_chain)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|error| NodestyError::config("client_identity", error.to_string()))?;
+            let key = PrivateKeyDer::from_pem_slice(private_key)
+                .map_err(|error| NodestyError::config("client_identity", error.to_string()))?;
+            builder
+                .with_client_auth_cert(chain, key)
+                .map_err(|error| NodestyError::config("client_identity", error.to_string()))?
+        }
+        Some(ClientIdentity::Pkcs12 { .. }) => {
+            return Err(NodestyError::config(
+                "client_identity",
+                "PKCS#12 identities cannot be combined with certificate pinning, use a PEM identity",
+            ));
+        }
+    };
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(config)
+}
+
+fn decode_pin(pin: &str) -> Result<[u8; 32], NodestyError> {
+    let encoded = pin
+        .trim()
+        .trim_start_matches("sha256/")
+        .trim_start_matches('/');
+    STANDARD
+        .decode(encoded)
+        .ok()
+        .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+        .ok_or_else(|| {
+            NodestyError::config(
+                "pinned_spki_sha256",
+                format!("`{}` is not a base64-encoded SHA-256 hash", pin),
+            )
+        })
+}
+
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    pins: Vec<[u8; 32]>,
+}
+
+impl PinnedVerifier {
+    fn is_pinned(&self, certificate: &CertificateDer<'_>) -> bool {
+        webpki::EndEntityCert::try_from(certificate).is_ok_and(|certificate| {
+            let spki = certificate.subject_public_key_info();
+            let hash = ring::digest::digest(&ring::digest::SHA256, spki.as_ref());
+            self.pins.iter().any(|pin| pin[..] == *hash.as_ref())
+        })
+    }
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+
+        if std::iter::once(end_entity)
+            .chain(intermediates)
+            .any(|certificate| self.is_pinned(certificate))
+        {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::General(
+                "server certificate chain does not match any pinned public key".to_string(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIN: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+
+    fn field(error: NodestyError) -> String {
+        match error {
+            NodestyError::Config { field, .. } => field,
+            other => panic!("expected Config, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pins_are_decoded_with_or_without_prefix() {
+        let expected: Vec<u8> = (0..32).collect();
+        for pin in [PIN.to_string(), format!("sha256/{}", PIN), format!("  {}\n", PIN)] {
+            assert_eq!(decode_pin(&pin).unwrap()[..], expected[..], "{}", pin);
+        }
+    }
+
+    #[test]
+    fn malformed_pins_are_rejected() {
+        for pin in ["", "not base64!", "AAECAwQ=", "sha256/AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwd"] {
+            assert_eq!(field(decode_pin(pin).unwrap_err()), "pinned_spki_sha256", "{}", pin);
+        }
+    }
+
+    #[test]
+    fn pinned_config_checks_pins_and_identity() {
+        let config = pinned_config(&[], None, &[PIN.to_string()]).unwrap();
+        assert_eq!(config.alpn_protocols, [b"h2".to_vec(), b"http/1.1".to_vec()]);
+
+        let error = pinned_config(&[], None, &["bad".to_string()]).unwrap_err();
+        assert_eq!(field(error), "pinned_spki_sha256");
+
+        let identity = ClientIdentity::Pkcs12 {
+            der: Vec::new(),
+            password: "secret".into(),
+        };
+        let error = pinned_config(&[], Some(&identity), &[PIN.to_string()]).unwrap_err();
+        assert_eq!(field(error), "client_identity");
+    }
+}
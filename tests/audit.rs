@@ -0,0 +1,87 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{builder, options, response, FakeTransport};
+use nodesty_api_library::{ChannelSink, JsonlFileSink, VpsAction, VpsApiService, VpsReinstallData};
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn completed_mutation_is_audited() {
+    let transport = FakeTransport::new([response(204, None, "")]);
+    let (sink, mut records) = ChannelSink::channel();
+    let client = builder(&transport, options())
+        .audit_sink(sink)
+        .audit_actor("inventory-job")
+        .build()
+        .unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    vps.perform_action("42", VpsAction::Restart).await.unwrap();
+
+    let record = records.try_recv().unwrap();
+    assert_eq!(record.method, Method::POST);
+    assert_eq!(record.operation, Some("VpsApiService::perform_action"));
+    assert_eq!(record.template, "/services/{id}/vps/action");
+    assert_eq!(record.service_id.as_deref(), Some("42"));
+    assert_eq!(record.body, Some(json!({"action": "restart"})));
+    assert_eq!(record.status, Some(204));
+    assert_eq!(record.error, None);
+    assert_eq!(record.actor.as_deref(), Some("inventory-job"));
+    assert!(records.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn dropped_mutation_is_still_audited() {
+    let transport = FakeTransport::default();
+    let (sink, mut records) = ChannelSink::channel();
+    let client = builder(&transport, options()).audit_sink(sink).build().unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    let call = vps.perform_action("42", VpsAction::Restart);
+    assert!(tokio::time::timeout(Duration::from_millis(20), call).await.is_err());
+    assert_eq!(transport.requests().len(), 1);
+
+    let record = records.try_recv().unwrap();
+    assert_eq!(record.method, Method::POST);
+    assert_eq!(record.operation, Some("VpsApiService::perform_action"));
+    assert_eq!(record.service_id.as_deref(), Some("42"));
+    assert_eq!(record.status, None);
+    assert_eq!(record.error, Some("cancelled"));
+    assert!(records.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn file_sink_writes_one_redacted_line_per_record() {
+    let path = std::env::temp_dir().join(format!("nodesty-audit-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let transport = FakeTransport::new([response(204, None, ""), response(204, None, "")]);
+    let client = builder(&transport, options())
+        .audit_sink(JsonlFileSink::open(&path).unwrap())
+        .build()
+        .unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    vps.perform_action("42", VpsAction::Stop).await.unwrap();
+    let data = VpsReinstallData {
+        password: "hunter2".to_string(),
+        os_id: 7,
+    };
+    vps.reinstall("42", data).await.unwrap();
+    // Dropping the client drops the sink, which waits for the writer thread.
+    drop(vps);
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines: Vec<Value> = contents.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["operation"], "VpsApiService::perform_action");
+    assert_eq!(lines[0]["status"], 204);
+    assert_eq!(lines[1]["operation"], "VpsApiService::reinstall");
+    assert_eq!(lines[1]["body"], json!({"password": "***", "osId": 7}));
+    assert!(!contents.contains("hunter2"));
+}
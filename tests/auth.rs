@@ -0,0 +1,174 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{builder, json, options, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::transport::BoxFuture;
+use nodesty_api_library::{
+    AccessToken, EnvTokenProvider, FileTokenProvider, HttpResponse, NodestyError, TokenProvider,
+    VpsApiService,
+};
+use reqwest::header::AUTHORIZATION;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Hands out `current` until refreshed, then `next`, or fails the refresh if there is none.
+#[derive(Clone)]
+struct Rotating {
+    current: Arc<Mutex<String>>,
+    next: Option<&'static str>,
+    rejected: Arc<Mutex<Vec<String>>>,
+}
+
+impl Rotating {
+    fn new(current: &str, next: Option<&'static str>) -> Self {
+        Self {
+            current: Arc::new(Mutex::new(current.to_string())),
+            next,
+            rejected: Arc::default(),
+        }
+    }
+}
+
+impl TokenProvider for Rotating {
+    fn token(&self) -> BoxFuture<'_, Result<AccessToken, NodestyError>> {
+        Box::pin(async move { Ok(AccessToken::new(self.current.lock().unwrap().clone())) })
+    }
+
+    fn refresh<'a>(
+        &'a self,
+        rejected: &'a AccessToken,
+    ) -> BoxFuture<'a, Result<AccessToken, NodestyError>> {
+        Box::pin(async move {
+            self.rejected.lock().unwrap().push(rejected.expose().to_string());
+            let next = self.next.ok_or_else(|| NodestyError::Config {
+                field: "token".to_string(),
+                message: "the vault is sealed".to_string(),
+            })?;
+            *self.current.lock().unwrap() = next.to_string();
+            Ok(AccessToken::new(next))
+        })
+    }
+}
+
+fn unauthorized() -> HttpResponse {
+    json(401, r#"{"message": "invalid token"}"#)
+}
+
+#[tokio::test]
+async fn unauthorized_request_is_replayed_with_the_refreshed_token() {
+    let provider = Rotating::new("old-token", Some("new-token"));
+    let transport = FakeTransport::new([unauthorized(), json(200, OS_TEMPLATES)]);
+    let client = builder(&transport, options()).token_provider(provider.clone()).build().unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    let templates = vps.get_os_templates("42").await.unwrap().into_result().unwrap();
+    assert_eq!(templates.len(), 2);
+    assert_eq!(*provider.rejected.lock().unwrap(), ["old-token"]);
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].headers[AUTHORIZATION], "PAT old-token");
+    assert_eq!(requests[1].headers[AUTHORIZATION], "PAT new-token");
+}
+
+#[tokio::test]
+async fn unchanged_token_is_not_replayed() {
+    let provider = Rotating::new("same-token", Some("same-token"));
+    let transport = FakeTransport::new([unauthorized()]);
+    let client = builder(&transport, options()).token_provider(provider.clone()).build().unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    let error = vps.get_os_templates("42").await.unwrap_err();
+    assert!(matches!(error, NodestyError::Unauthorized { .. }), "{:?}", error);
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn failed_refresh_keeps_the_unauthorized_error() {
+    let provider = Rotating::new("old-token", None);
+    let transport = FakeTransport::new([unauthorized()]);
+    let client = builder(&transport, options()).token_provider(provider.clone()).build().unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    match vps.get_os_templates("42").await {
+        Err(NodestyError::Unauthorized { message }) => {
+            assert_eq!(message.as_deref(), Some("invalid token"));
+        }
+        other => panic!("expected Unauthorized, got {:?}", other),
+    }
+    assert_eq!(*provider.rejected.lock().unwrap(), ["old-token"]);
+    assert_eq!(transport.requests().len(), 1);
+}
+
+/// A path unique to this test binary and test.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("nodesty-auth-{}-{}", std::process::id(), name))
+}
+
+#[tokio::test]
+async fn file_provider_reloads_once_per_rejected_token() {
+    let path = temp_path("file");
+    std::fs::write(&path, "old-token\n").unwrap();
+    let provider = FileTokenProvider::new(&path).with_check_interval(Duration::from_secs(60));
+
+    let old = provider.token().await.unwrap();
+    assert_eq!(old.expose(), "old-token");
+
+    // Within the check interval the file is not looked at.
+    std::fs::write(&path, "new-token").unwrap();
+    assert_eq!(provider.token().await.unwrap().expose(), "old-token");
+
+    let new = provider.refresh(&old).await.unwrap();
+    assert_eq!(new.expose(), "new-token");
+
+    // Other requests that were rejected with the old token get the new one without a reread.
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(provider.refresh(&old).await.unwrap().expose(), "new-token");
+
+    let error = provider.refresh(&new).await.unwrap_err();
+    assert!(matches!(error, NodestyError::Config { .. }), "{:?}", error);
+}
+
+#[tokio::test]
+async fn env_provider_reads_the_variable() {
+    // SAFETY: no other test in this binary reads the environment.
+    unsafe { std::env::set_var("NODESTY_AUTH_TEST_TOKEN", " env-token\n") };
+    let provider = EnvTokenProvider::new("NODESTY_AUTH_TEST_TOKEN");
+    assert_eq!(provider.token().await.unwrap().expose(), "env-token");
+
+    unsafe { std::env::remove_var("NODESTY_AUTH_TEST_TOKEN") };
+    match provider.token().await {
+        Err(NodestyError::Config { field, message }) => {
+            assert_eq!(field, "NODESTY_AUTH_TEST_TOKEN");
+            assert_eq!(message, "is not set");
+        }
+        other => panic!("expected Config, got {:?}", other),
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn command_provider_runs_once_for_concurrent_refreshes() {
+    use nodesty_api_library::CommandTokenProvider;
+
+    let runs = temp_path("runs");
+    let script = format!(r#"echo run >> "{0}"; echo "token-$(wc -l < "{0}" | tr -d ' ')""#, runs.display());
+    let provider = CommandTokenProvider::new("sh").arg("-c").arg(script);
+
+    let first = provider.token().await.unwrap();
+    assert_eq!(first.expose(), "token-1");
+    assert_eq!(provider.token().await.unwrap(), first);
+
+    let (a, b, c) = tokio::join!(
+        provider.refresh(&first),
+        provider.refresh(&first),
+        provider.refresh(&first)
+    );
+    for token in [a, b, c] {
+        assert_eq!(token.unwrap().expose(), "token-2");
+    }
+    assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 2);
+    std::fs::remove_file(runs).unwrap();
+}
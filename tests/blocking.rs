@@ -0,0 +1,57 @@
+#![cfg(all(feature = "blocking", feature = "vps"))]
+
+mod common;
+
+use common::{builder, json, options, response, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::blocking::{NodestyApiClient, VpsApiService};
+use nodesty_api_library::{NodestyError, VpsAction, VpsOsTemplate};
+use reqwest::Method;
+use std::sync::Arc;
+
+fn client(transport: &FakeTransport) -> Arc<NodestyApiClient> {
+    let client = builder(transport, options()).build().unwrap();
+    Arc::new(NodestyApiClient::from_client(client).unwrap())
+}
+
+#[test]
+fn service_calls_block_until_the_response() {
+    let transport = FakeTransport::new([json(200, OS_TEMPLATES), response(204, None, "")]);
+    let vps = VpsApiService::new(client(&transport));
+
+    let templates = vps.get_os_templates("42").unwrap().into_result().unwrap();
+    assert_eq!(templates.len(), 2);
+    assert_eq!(templates[0].name, "Debian 12");
+
+    let response = vps.perform_action("42", VpsAction::Start).unwrap();
+    assert_eq!(response.data, Some(()));
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].method, Method::GET);
+    assert!(requests[0].url.ends_with("/services/42/vps/os-templates"));
+    assert_eq!(requests[1].method, Method::POST);
+    assert!(requests[1].url.ends_with("/services/42/vps/action"));
+}
+
+#[test]
+fn errors_are_returned_as_is() {
+    let transport = FakeTransport::new([json(404, r#"{"message": "no such service"}"#)]);
+    let vps = VpsApiService::new(client(&transport));
+
+    let error = vps.get_details("42").unwrap_err();
+    assert_eq!(error.kind(), "not_found");
+    assert!(matches!(error, NodestyError::NotFound { .. }), "{:?}", error);
+}
+
+#[test]
+fn send_request_reaches_any_route() {
+    let transport = FakeTransport::new([json(200, OS_TEMPLATES)]);
+    let client = client(&transport);
+
+    let templates = client
+        .send_request::<Vec<VpsOsTemplate>>(Method::GET, "/services/42/vps/os-templates", None)
+        .unwrap()
+        .into_result()
+        .unwrap();
+    assert_eq!(templates[1].name, "Ubuntu 24.04");
+    assert!(transport.requests()[0].url.ends_with("/services/42/vps/os-templates"));
+}
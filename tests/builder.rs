@@ -0,0 +1,93 @@
+mod common;
+
+use common::{options, FakeTransport};
+use nodesty_api_library::{
+    AccessToken, NodestyApiClient, NodestyError, ProxyConfig, RestClientOptions,
+};
+
+fn config_error(result: Result<NodestyApiClient, NodestyError>) -> (String, String) {
+    match result {
+        Err(NodestyError::Config { field, message }) => (field, message),
+        other => panic!("expected Config, got {:?}", other),
+    }
+}
+
+#[test]
+fn token_is_required() {
+    let (field, message) = config_error(NodestyApiClient::builder().build());
+    assert_eq!(field, "access_token");
+    assert_eq!(message, "an access token is required");
+
+    let (field, message) = config_error(NodestyApiClient::new(RestClientOptions::new("  ")));
+    assert_eq!(field, "access_token");
+    assert_eq!(message, "must not be empty");
+}
+
+#[test]
+fn token_must_fit_in_a_header() {
+    let (field, _) = config_error(NodestyApiClient::builder().access_token("line\nbreak").build());
+    assert_eq!(field, "access_token");
+}
+
+#[test]
+fn invalid_options_are_rejected() {
+    let (field, _) = config_error(NodestyApiClient::new(options().with_timeout_ms(0)));
+    assert_eq!(field, "timeout_ms");
+
+    let (field, message) = config_error(NodestyApiClient::new(options().with_retry_delay_ms(50, 10)));
+    assert_eq!(field, "retry_base_delay_ms");
+    assert_eq!(message, "must not be greater than retry_max_delay_ms");
+
+    let (field, _) = config_error(NodestyApiClient::new(options().with_max_concurrency(0)));
+    assert_eq!(field, "max_concurrency");
+
+    let (field, message) = config_error(NodestyApiClient::new(options().with_base_url("ftp://nodesty.com")));
+    assert_eq!(field, "base_url");
+    assert!(message.contains("unsupported scheme"));
+}
+
+#[test]
+fn network_options_conflict_with_a_custom_transport() {
+    let result = NodestyApiClient::builder()
+        .options(options().with_proxy("http://proxy.internal:3128"))
+        .transport(FakeTransport::default())
+        .build();
+    let (field, message) = config_error(result);
+    assert_eq!(field, "proxy");
+    assert_eq!(message, "cannot be combined with a custom transport or http client");
+}
+
+#[test]
+fn base_url_is_normalized() {
+    let client = NodestyApiClient::builder()
+        .access_token("test-token")
+        .base_url("nodesty.com/api/")
+        .transport(FakeTransport::default())
+        .build()
+        .unwrap();
+    assert!(format!("{:?}", client).contains("\"https://nodesty.com/api\""));
+}
+
+#[test]
+fn debug_output_redacts_secrets() {
+    let token = AccessToken::new("secret-token");
+    assert_eq!(format!("{:?}", token), "AccessToken(***)");
+    assert_eq!(token.to_string(), "***");
+    assert_eq!(token.expose(), "secret-token");
+
+    let options = RestClientOptions::new("secret-token")
+        .with_proxy(ProxyConfig::new("http://proxy.internal:3128").with_basic_auth("user", "secret-password"));
+    let builder = NodestyApiClient::builder().options(options.clone()).access_token("secret-token");
+
+    for output in [format!("{:?}", options), format!("{:?}", builder)] {
+        assert!(!output.contains("secret-token"), "{}", output);
+        assert!(!output.contains("secret-password"), "{}", output);
+    }
+}
+
+#[test]
+fn malformed_pin_is_rejected() {
+    let options = options().with_pinned_spki_sha256("not a pin");
+    let (field, _) = config_error(NodestyApiClient::new(options));
+    assert_eq!(field, "pinned_spki_sha256");
+}
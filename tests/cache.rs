@@ -0,0 +1,110 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{builder, json, options, response, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::transport::BoxFuture;
+use nodesty_api_library::{
+    CacheConfig, HttpRequest, HttpResponse, Middleware, Next, NodestyError, RestClientOptions,
+    VpsAction, VpsApiService,
+};
+use reqwest::header::{HeaderValue, ETAG, IF_NONE_MATCH};
+use reqwest::Method;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+fn cached() -> RestClientOptions {
+    options().with_cache(CacheConfig::new(Duration::from_secs(60)))
+}
+
+#[tokio::test]
+async fn gets_are_served_from_the_cache_until_a_mutation() {
+    let transport = FakeTransport::new([
+        json(200, OS_TEMPLATES),
+        response(204, None, ""),
+        json(200, OS_TEMPLATES),
+    ]);
+    let vps = VpsApiService::new(common::client(&transport, cached()));
+
+    vps.get_os_templates("42").await.unwrap();
+    vps.get_os_templates("42").await.unwrap();
+    assert_eq!(transport.requests().len(), 1);
+
+    vps.perform_action("42", VpsAction::Restart).await.unwrap();
+    vps.get_os_templates("42").await.unwrap();
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn stale_entries_are_revalidated() {
+    let mut tagged = json(200, OS_TEMPLATES);
+    tagged.headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+    let transport = FakeTransport::new([tagged, response(304, None, "")]);
+    let options = options().with_cache(CacheConfig::new(Duration::from_millis(1)));
+    let vps = VpsApiService::new(common::client(&transport, options));
+
+    vps.get_os_templates("42").await.unwrap();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let templates = vps.get_os_templates("42").await.unwrap().into_result().unwrap();
+    assert_eq!(templates.len(), 2);
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].headers[IF_NONE_MATCH], "\"v1\"");
+}
+
+/// Holds the first GET back until `release` is notified, after notifying `entered`.
+struct HoldFirstGet {
+    held: AtomicBool,
+    entered: Arc<Notify>,
+    release: Arc<Notify>,
+}
+
+impl Middleware for HoldFirstGet {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, NodestyError>> {
+        Box::pin(async move {
+            if request.method == Method::GET && !self.held.swap(true, Ordering::SeqCst) {
+                self.entered.notify_one();
+                self.release.notified().await;
+            }
+            next.run(request).await
+        })
+    }
+}
+
+#[tokio::test]
+async fn get_in_flight_during_a_mutation_is_not_cached() {
+    let transport = FakeTransport::new([
+        response(204, None, ""),
+        json(200, OS_TEMPLATES),
+        json(200, OS_TEMPLATES),
+    ]);
+    let (entered, release) = (Arc::new(Notify::new()), Arc::new(Notify::new()));
+    let client = builder(&transport, cached())
+        .middleware(HoldFirstGet {
+            held: AtomicBool::new(false),
+            entered: entered.clone(),
+            release: release.clone(),
+        })
+        .build()
+        .unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    let (get, ()) = tokio::join!(vps.get_os_templates("42"), async {
+        entered.notified().await;
+        vps.perform_action("42", VpsAction::Restart).await.unwrap();
+        release.notify_one();
+    });
+    get.unwrap();
+
+    // The first GET started before the restart finished, so its response must not be served.
+    vps.get_os_templates("42").await.unwrap();
+    let methods: Vec<_> = transport.requests().into_iter().map(|request| request.method).collect();
+    assert_eq!(methods, [Method::POST, Method::GET, Method::GET]);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{builder, json, options, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::{
+    CircuitBreakerConfig, CircuitState, HttpResponse, NodestyError, VpsApiService,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn outage() -> HttpResponse {
+    json(503, r#"{"message": "maintenance"}"#)
+}
+
+#[tokio::test]
+async fn open_circuit_fails_fast_until_a_trial_succeeds() {
+    let transport = FakeTransport::new([outage(), outage(), json(200, OS_TEMPLATES)]);
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let recorded = changes.clone();
+    let options = options()
+        .with_retry(0)
+        .with_circuit_breaker(CircuitBreakerConfig::new(2, Duration::from_millis(50)));
+    let client = builder(&transport, options)
+        .on_circuit_change(move |event| recorded.lock().unwrap().push(event.to))
+        .build()
+        .unwrap();
+    let client = Arc::new(client);
+    let vps = VpsApiService::new(client.clone());
+
+    for _ in 0..2 {
+        vps.get_os_templates("42").await.unwrap_err();
+    }
+    assert_eq!(client.circuit_state("VpsApiService"), CircuitState::Open);
+
+    match vps.get_os_templates("42").await {
+        Err(NodestyError::CircuitOpen { group, retry_after }) => {
+            assert_eq!(group, "VpsApiService");
+            assert!(retry_after.is_some());
+        }
+        other => panic!("expected CircuitOpen, got {:?}", other),
+    }
+    assert_eq!(transport.requests().len(), 2);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    vps.get_os_templates("42").await.unwrap();
+    assert_eq!(client.circuit_state("VpsApiService"), CircuitState::Closed);
+    assert_eq!(
+        *changes.lock().unwrap(),
+        [CircuitState::Open, CircuitState::HalfOpen, CircuitState::Closed]
+    );
+}
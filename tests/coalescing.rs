@@ -0,0 +1,49 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{builder, json, options, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::transport::BoxFuture;
+use nodesty_api_library::{HttpRequest, HttpResponse, Middleware, Next, NodestyError, VpsApiService};
+use std::sync::Arc;
+
+/// Yields once before sending, so concurrent calls are all in flight at the same time.
+struct YieldFirst;
+
+impl Middleware for YieldFirst {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, NodestyError>> {
+        Box::pin(async move {
+            tokio::task::yield_now().await;
+            next.run(request).await
+        })
+    }
+}
+
+#[tokio::test]
+async fn identical_gets_in_flight_share_one_request() {
+    let transport = FakeTransport::new([json(200, OS_TEMPLATES), json(200, OS_TEMPLATES)]);
+    let client = builder(&transport, options().with_request_coalescing(true))
+        .middleware(YieldFirst)
+        .build()
+        .unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    let (first, second, other) = tokio::join!(
+        vps.get_os_templates("42"),
+        vps.get_os_templates("42"),
+        vps.get_os_templates("43"),
+    );
+    assert_eq!(first.unwrap().into_result().unwrap().len(), 2);
+    assert_eq!(second.unwrap().into_result().unwrap().len(), 2);
+    assert_eq!(other.unwrap().into_result().unwrap().len(), 2);
+
+    let mut urls: Vec<_> = transport.requests().into_iter().map(|request| request.url).collect();
+    urls.sort();
+    assert_eq!(urls.len(), 2);
+    assert!(urls[0].ends_with("/services/42/vps/os-templates"));
+    assert!(urls[1].ends_with("/services/43/vps/os-templates"));
+}
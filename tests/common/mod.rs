@@ -0,0 +1,97 @@
+#![allow(dead_code)]
+
+use nodesty_api_library::transport::BoxFuture;
+use nodesty_api_library::{
+    HttpRequest, HttpResponse, NodestyApiClient, NodestyApiClientBuilder, RestClientOptions,
+    Transport, TransportError,
+};
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::StatusCode;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Answers requests with canned responses, in order, and keeps every request it was sent.
+/// Once the responses run out, requests never complete.
+#[derive(Clone, Default)]
+pub struct FakeTransport {
+    responses: Arc<Mutex<VecDeque<HttpResponse>>>,
+    requests: Arc<Mutex<Vec<HttpRequest>>>,
+}
+
+impl FakeTransport {
+    pub fn new(responses: impl IntoIterator<Item = HttpResponse>) -> Self {
+        Self {
+            responses: Arc::new(Mutex::new(responses.into_iter().collect())),
+            requests: Arc::default(),
+        }
+    }
+
+    pub fn push(&self, response: HttpResponse) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for FakeTransport {
+    fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, TransportError>> {
+        self.requests.lock().unwrap().push(request);
+        let response = self.responses.lock().unwrap().pop_front();
+        Box::pin(async move {
+            match response {
+                Some(response) => Ok(response),
+                None => std::future::pending().await,
+            }
+        })
+    }
+}
+
+pub fn response(status: u16, content_type: Option<&'static str>, body: &str) -> HttpResponse {
+    let mut headers = HeaderMap::new();
+    if let Some(content_type) = content_type {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+    }
+    HttpResponse {
+        status: StatusCode::from_u16(status).unwrap(),
+        headers,
+        body: body.as_bytes().to_vec(),
+    }
+}
+
+pub fn json(status: u16, body: &str) -> HttpResponse {
+    response(status, Some("application/json"), body)
+}
+
+/// Options with a test token and retry delays short enough to keep tests fast.
+pub fn options() -> RestClientOptions {
+    RestClientOptions::new("test-token").with_retry_delay_ms(1, 5)
+}
+
+pub fn builder(transport: &FakeTransport, options: RestClientOptions) -> NodestyApiClientBuilder {
+    NodestyApiClient::builder().options(options).transport(transport.clone())
+}
+
+pub fn client(transport: &FakeTransport, options: RestClientOptions) -> Arc<NodestyApiClient> {
+    Arc::new(builder(transport, options).build().unwrap())
+}
+
+pub const OS_TEMPLATES: &str = r#"[{"id": 1, "name": "Debian 12"}, {"id": 2, "name": "Ubuntu 24.04"}]"#;
+
+pub const VPS_DETAILS: &str = r#"{
+    "vpsId": 42, "proxmoxId": 1042, "hostname": "vps42.example.com", "osReinstallLimit": 5,
+    "status": true,
+    "vnc": {"enabled": false, "ip": "198.51.100.1", "port": "5900", "password": "vnc-password"},
+    "os": {"name": "Debian 12", "distro": "debian"},
+    "disk": {"limit": 100, "used": 40, "free": 60, "percent": 40},
+    "ips": ["203.0.113.7", "203.0.113.8"],
+    "cpu": {"manu": "AMD", "limit": 4000, "used": 1000, "free": 3000, "percent": 25.0, "cores": 4},
+    "ram": {"limit": 8192, "used": 2048, "free": 6144, "percent": 25},
+    "inode": {"limit": 1000, "used": 100, "free": 900, "percent": 10},
+    "netspeed": {"in": 1000, "out": 1000},
+    "bandwidth": {
+        "total": {"usage": 10, "in": 6, "out": 4},
+        "usage": [], "in": [], "out": [], "categories": []
+    }
+}"#;
@@ -0,0 +1,76 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{builder, json, options, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::transport::BoxFuture;
+use nodesty_api_library::{
+    HttpRequest, HttpResponse, Middleware, Next, NodestyError, Priority, VpsApiService,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Holds the first request, and with it the only concurrency slot, until `release` is notified.
+struct HoldFirst {
+    held: AtomicBool,
+    entered: Arc<Notify>,
+    release: Arc<Notify>,
+}
+
+impl Middleware for HoldFirst {
+    fn handle<'a>(
+        &'a self,
+        request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, NodestyError>> {
+        Box::pin(async move {
+            if !self.held.swap(true, Ordering::SeqCst) {
+                self.entered.notify_one();
+                self.release.notified().await;
+            }
+            next.run(request).await
+        })
+    }
+}
+
+#[tokio::test]
+async fn queued_calls_run_by_priority() {
+    let transport = FakeTransport::new([
+        json(200, OS_TEMPLATES),
+        json(200, OS_TEMPLATES),
+        json(200, OS_TEMPLATES),
+    ]);
+    let (entered, release) = (Arc::new(Notify::new()), Arc::new(Notify::new()));
+    let client = builder(&transport, options().with_max_concurrency(1))
+        .middleware(HoldFirst {
+            held: AtomicBool::new(false),
+            entered: entered.clone(),
+            release: release.clone(),
+        })
+        .build()
+        .unwrap();
+    let client = Arc::new(client);
+    let vps = VpsApiService::new(client.clone());
+
+    let (first, low, high, ()) = tokio::join!(
+        vps.get_os_templates("1"),
+        Priority::Low.scope(vps.get_os_templates("2")),
+        Priority::High.scope(vps.get_os_templates("3")),
+        async {
+            entered.notified().await;
+            assert_eq!(client.active_requests(), 1);
+            assert_eq!(client.queued_requests(), 2);
+            release.notify_one();
+        },
+    );
+    for result in [first, low, high] {
+        result.unwrap();
+    }
+
+    let urls: Vec<_> = transport.requests().into_iter().map(|request| request.url).collect();
+    assert!(urls[0].ends_with("/services/1/vps/os-templates"));
+    assert!(urls[1].ends_with("/services/3/vps/os-templates"), "{:?}", urls);
+    assert!(urls[2].ends_with("/services/2/vps/os-templates"), "{:?}", urls);
+    assert_eq!(client.active_requests(), 0);
+}
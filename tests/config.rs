@@ -0,0 +1,178 @@
+use nodesty_api_library::{NodestyError, RestClientOptions};
+use std::sync::Mutex;
+
+/// The tests change process-wide environment variables, so they take turns.
+static ENV: Mutex<()> = Mutex::new(());
+
+const VARS: [&str; 8] = [
+    "NODESTY_API_TOKEN",
+    "NODESTY_BASE_URL",
+    "NODESTY_TIMEOUT_MS",
+    "NODESTY_RETRY",
+    "NODESTY_PROXY",
+    "NODESTY_DRY_RUN",
+    "NODESTY_CONFIG",
+    "NODESTY_PROFILE",
+];
+
+/// Runs `test` with exactly the given `NODESTY_*` variables set.
+fn with_env(vars: &[(&str, &str)], test: impl FnOnce()) {
+    let _guard = ENV.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // SAFETY: every test that touches the environment holds `ENV`, and nothing else in this
+    // test binary reads it concurrently.
+    unsafe {
+        for var in VARS {
+            std::env::remove_var(var);
+        }
+        for (var, value) in vars {
+            std::env::set_var(var, value);
+        }
+    }
+    test();
+}
+
+fn config_error<T: std::fmt::Debug>(result: Result<T, NodestyError>) -> (String, String) {
+    match result {
+        Err(NodestyError::Config { field, message }) => (field, message),
+        other => panic!("expected Config, got {:?}", other),
+    }
+}
+
+#[test]
+fn options_are_read_from_the_environment() {
+    let vars = [
+        ("NODESTY_API_TOKEN", "env-token"),
+        ("NODESTY_BASE_URL", "http://localhost:8080/api"),
+        ("NODESTY_TIMEOUT_MS", " 2500 "),
+        ("NODESTY_RETRY", "4"),
+        ("NODESTY_PROXY", "http://proxy.internal:3128"),
+        ("NODESTY_DRY_RUN", "true"),
+    ];
+    with_env(&vars, || {
+        let options = RestClientOptions::from_env().unwrap();
+        assert_eq!(options.access_token.expose(), "env-token");
+        assert_eq!(options.base_url, "http://localhost:8080/api");
+        assert_eq!(options.timeout_ms, Some(2500));
+        assert_eq!(options.retry, Some(4));
+        assert_eq!(options.proxy.unwrap().url, "http://proxy.internal:3128");
+        assert!(options.dry_run);
+    });
+}
+
+#[test]
+fn environment_errors_name_the_variable() {
+    with_env(&[("NODESTY_API_TOKEN", " ")], || {
+        let (field, message) = config_error(RestClientOptions::from_env());
+        assert_eq!(field, "NODESTY_API_TOKEN");
+        assert_eq!(message, "is not set");
+    });
+
+    with_env(&[("NODESTY_API_TOKEN", "env-token"), ("NODESTY_RETRY", "often")], || {
+        let (field, message) = config_error(RestClientOptions::from_env());
+        assert_eq!(field, "NODESTY_RETRY");
+        assert!(message.starts_with("`often`: "), "{}", message);
+    });
+
+    with_env(&[("NODESTY_API_TOKEN", "env-token"), ("NODESTY_DRY_RUN", "yes")], || {
+        let (field, _) = config_error(RestClientOptions::from_env());
+        assert_eq!(field, "NODESTY_DRY_RUN");
+    });
+}
+
+#[cfg(feature = "profile")]
+mod profile {
+    use super::*;
+    use std::path::PathBuf;
+
+    const CONFIG: &str = r#"
+[default]
+token = "default-token"
+timeout_ms = 1000
+
+[staging]
+token = "staging-token"
+base_url = "https://staging.nodesty.com/api"
+retry = 2
+
+[staging.proxy]
+url = "http://proxy.internal:3128"
+username = "user"
+password = "secret"
+
+[typo]
+token = "typo-token"
+timeout = 1000
+
+[empty]
+"#;
+
+    /// Writes `contents` to a file unique to this test binary and test.
+    fn config_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("nodesty-config-{}-{}.toml", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn profiles_are_selected_by_name() {
+        let path = config_file("select", CONFIG);
+        with_env(&[], || {
+            let options = RestClientOptions::from_profile_file(&path, None).unwrap();
+            assert_eq!(options.access_token.expose(), "default-token");
+            assert_eq!(options.timeout_ms, Some(1000));
+
+            let options = RestClientOptions::from_profile_file(&path, Some("staging")).unwrap();
+            assert_eq!(options.access_token.expose(), "staging-token");
+            assert_eq!(options.base_url, "https://staging.nodesty.com/api");
+            assert_eq!(options.retry, Some(2));
+            let proxy = options.proxy.unwrap();
+            assert_eq!(proxy.url, "http://proxy.internal:3128");
+            let (username, password) = proxy.basic_auth.unwrap();
+            assert_eq!(username, "user");
+            assert_eq!(password.expose(), "secret");
+        });
+
+        let config = path.display().to_string();
+        with_env(&[("NODESTY_CONFIG", &config), ("NODESTY_PROFILE", "staging")], || {
+            let options = RestClientOptions::from_profile(None).unwrap();
+            assert_eq!(options.access_token.expose(), "staging-token");
+        });
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn environment_overrides_the_profile() {
+        let path = config_file("override", CONFIG);
+        let vars = [("NODESTY_API_TOKEN", "env-token"), ("NODESTY_TIMEOUT_MS", "5000")];
+        with_env(&vars, || {
+            let options = RestClientOptions::from_profile_file(&path, Some("default")).unwrap();
+            assert_eq!(options.access_token.expose(), "env-token");
+            assert_eq!(options.timeout_ms, Some(5000));
+        });
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn profile_errors_name_the_key() {
+        let path = config_file("errors", CONFIG);
+        with_env(&[], || {
+            let (field, message) = config_error(RestClientOptions::from_profile_file(&path, Some("prod")));
+            assert_eq!(field, "prod");
+            assert!(message.starts_with("profile not found in "), "{}", message);
+
+            let (field, message) = config_error(RestClientOptions::from_profile_file(&path, Some("typo")));
+            assert_eq!(field, "typo.timeout");
+            assert!(message.starts_with("unknown field `timeout`"), "{}", message);
+
+            let (field, message) = config_error(RestClientOptions::from_profile_file(&path, Some("empty")));
+            assert_eq!(field, "empty.token");
+            assert!(message.ends_with("and NODESTY_API_TOKEN is empty"), "{}", message);
+
+            let missing = path.with_extension("missing");
+            let (field, _) = config_error(RestClientOptions::from_profile_file(&missing, None));
+            assert_eq!(field, missing.display().to_string());
+        });
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{client, json, options, FakeTransport};
+use nodesty_api_library::{NodestyError, VpsApiService};
+
+#[tokio::test]
+async fn strict_decoding_reports_the_failing_path() {
+    let body = r#"[{"id": 1, "name": "Debian 12"}, {"id": "two", "name": "Ubuntu 24.04"}]"#;
+    let transport = FakeTransport::new([json(200, body)]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    match vps.get_os_templates("42").await {
+        Err(NodestyError::Decode { path, message, body }) => {
+            assert_eq!(path.as_deref(), Some("[1].id"));
+            assert!(message.contains("expected u32"));
+            assert!(body.is_some());
+        }
+        other => panic!("expected Decode, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn lenient_decoding_skips_values_that_do_not_fit() {
+    let body = r#"[{"id": 1, "name": "Debian 12"}, {"id": "two", "name": "Ubuntu 24.04"}]"#;
+    let transport = FakeTransport::new([json(200, body)]);
+    let vps = VpsApiService::new(client(&transport, options().with_lenient_decoding(true)));
+
+    let response = vps.get_os_templates("42").await.unwrap();
+    let templates = response.data.unwrap();
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].name, "Debian 12");
+    assert_eq!(
+        response.error.as_deref(),
+        Some("failed to decode response: [1].id: invalid type: string \"two\", expected u32")
+    );
+    assert!(response.raw.is_some());
+}
+
+#[tokio::test]
+async fn lenient_decoding_reports_errors_as_received() {
+    let body = r#"[{"id": "a", "name": "A"}, {"id": "b", "name": "B"}, {"id": 3, "name": "C"}]"#;
+    let transport = FakeTransport::new([json(200, body)]);
+    let vps = VpsApiService::new(client(&transport, options().with_lenient_decoding(true)));
+
+    let response = vps.get_os_templates("42").await.unwrap();
+    let templates = response.data.unwrap();
+    assert_eq!(templates.len(), 1);
+    assert_eq!(templates[0].id, 3);
+
+    let error = response.error.unwrap();
+    assert!(error.contains("[0].id: invalid type: string \"a\""), "{}", error);
+    assert!(error.contains("[1].id: invalid type: string \"b\""), "{}", error);
+    assert!(!error.contains("missing field"), "{}", error);
+}
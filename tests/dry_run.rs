@@ -0,0 +1,63 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{client, json, options, response, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::{VpsAction, VpsApiService, VpsReinstallData};
+use reqwest::Method;
+use serde_json::json;
+
+#[tokio::test]
+async fn mutations_are_recorded_instead_of_sent() {
+    let transport = FakeTransport::new([json(200, OS_TEMPLATES)]);
+    let client = client(&transport, options().with_dry_run(true));
+    let vps = VpsApiService::new(client.clone());
+    assert!(client.is_dry_run());
+
+    // Reads still go out.
+    let templates = vps.get_os_templates("42").await.unwrap().into_result().unwrap();
+    assert_eq!(templates.len(), 2);
+
+    let response = vps.perform_action("42", VpsAction::Restart).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.data, Some(()));
+    let data = VpsReinstallData {
+        password: "hunter2".to_string(),
+        os_id: 7,
+    };
+    vps.reinstall("43", data).await.unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, Method::GET);
+
+    let planned = client.planned_mutations();
+    assert_eq!(planned.len(), 2);
+    assert_eq!(planned[0].method, Method::POST);
+    assert_eq!(planned[0].operation, Some("VpsApiService::perform_action"));
+    assert_eq!(planned[0].template, "/services/{id}/vps/action");
+    assert_eq!(planned[0].path, "/services/42/vps/action");
+    assert_eq!(planned[0].service_id.as_deref(), Some("42"));
+    assert_eq!(planned[0].body, Some(json!({"action": "restart"})));
+    assert_eq!(planned[1].operation, Some("VpsApiService::reinstall"));
+    assert_eq!(planned[1].service_id.as_deref(), Some("43"));
+    assert_eq!(planned[1].body, Some(json!({"password": "hunter2", "osId": 7})));
+
+    let serialized = serde_json::to_value(&planned[0]).unwrap();
+    assert_eq!(serialized["method"], "POST");
+
+    assert_eq!(client.take_planned_mutations(), planned);
+    assert!(client.planned_mutations().is_empty());
+}
+
+#[tokio::test]
+async fn nothing_is_recorded_outside_dry_run() {
+    let transport = FakeTransport::new([response(204, None, "")]);
+    let client = client(&transport, options());
+    let vps = VpsApiService::new(client.clone());
+
+    vps.perform_action("42", VpsAction::Start).await.unwrap();
+    assert!(!client.is_dry_run());
+    assert!(client.planned_mutations().is_empty());
+    assert_eq!(transport.requests().len(), 1);
+}
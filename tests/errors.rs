@@ -0,0 +1,58 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{client, json, options, FakeTransport};
+use nodesty_api_library::{NodestyError, VpsAction, VpsApiService};
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn validation_errors_keep_their_status_and_fields() {
+    let body = r#"{"message": "invalid", "errors": {"password": ["too short", "too simple"]}}"#;
+    let transport = FakeTransport::new([json(422, body)]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    let error = vps.perform_action("42", VpsAction::Restart).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::UNPROCESSABLE_ENTITY));
+    assert_eq!(error.kind(), "validation");
+    assert_eq!(error.to_string(), "validation failed: invalid; password: too short, too simple");
+    match error {
+        NodestyError::Validation { message, fields, .. } => {
+            assert_eq!(message.as_deref(), Some("invalid"));
+            assert_eq!(fields["password"], ["too short", "too simple"]);
+        }
+        other => panic!("expected Validation, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn validation_errors_accept_a_list_of_fields() {
+    let body = r#"{"errors": [{"field": "hostname", "message": "taken"}]}"#;
+    let transport = FakeTransport::new([json(400, body)]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    let error = vps.perform_action("42", VpsAction::Start).await.unwrap_err();
+    assert_eq!(error.status(), Some(StatusCode::BAD_REQUEST));
+    match error {
+        NodestyError::Validation { fields, .. } => assert_eq!(fields["hostname"], ["taken"]),
+        other => panic!("expected Validation, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn statuses_map_to_variants() {
+    let cases = [
+        (401, "unauthorized"),
+        (403, "forbidden"),
+        (404, "not_found"),
+        (409, "api"),
+    ];
+    for (status, kind) in cases {
+        let transport = FakeTransport::new([json(status, r#"{"message": "nope"}"#)]);
+        let vps = VpsApiService::new(client(&transport, options().with_retry(0)));
+
+        let error = vps.get_details("42").await.unwrap_err();
+        assert_eq!(error.kind(), kind);
+        assert_eq!(error.status().map(|status| status.as_u16()), Some(status));
+    }
+}
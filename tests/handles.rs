@@ -0,0 +1,46 @@
+#![cfg(all(feature = "vps", feature = "firewall"))]
+
+mod common;
+
+use common::{client, json, options, response, FakeTransport, OS_TEMPLATES, VPS_DETAILS};
+use nodesty_api_library::{Nodesty, VpsApiService};
+use reqwest::Method;
+use serde_json::Value;
+
+#[tokio::test]
+async fn vps_handle_binds_the_id() {
+    let transport = FakeTransport::new([response(204, None, ""), json(200, OS_TEMPLATES)]);
+    let nodesty = Nodesty::from_client(client(&transport, options()));
+    let vps = nodesty.vps_handle("42");
+    assert_eq!(vps.id(), "42");
+
+    vps.restart().await.unwrap();
+    let templates = vps.get_os_templates().await.unwrap().into_result().unwrap();
+    assert_eq!(templates.len(), 2);
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].method, Method::POST);
+    assert!(requests[0].url.ends_with("/services/42/vps/action"));
+    let body: Value = serde_json::from_slice(requests[0].body.as_deref().unwrap()).unwrap();
+    assert_eq!(body, serde_json::json!({"action": "restart"}));
+    assert_eq!(requests[1].method, Method::GET);
+    assert!(requests[1].url.ends_with("/services/42/vps/os-templates"));
+}
+
+#[tokio::test]
+async fn firewall_handles_follow_the_vps_ips() {
+    let transport = FakeTransport::new([json(200, VPS_DETAILS), json(200, "[]")]);
+    let vps = VpsApiService::new(client(&transport, options())).handle("42");
+
+    let firewalls = vps.firewalls().await.unwrap();
+    let ips: Vec<_> = firewalls.iter().map(|firewall| firewall.ip()).collect();
+    assert_eq!(ips, ["203.0.113.7", "203.0.113.8"]);
+    assert!(firewalls.iter().all(|firewall| firewall.service_id() == "42"));
+
+    let rules = firewalls[1].get_rules().await.unwrap().into_result().unwrap();
+    assert!(rules.is_empty());
+
+    let requests = transport.requests();
+    assert!(requests[0].url.ends_with("/services/42/vps/info"), "{}", requests[0].url);
+    assert!(requests[1].url.ends_with("/services/42/firewall/203.0.113.8/rules"));
+}
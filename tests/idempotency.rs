@@ -0,0 +1,68 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{client, json, options, response, FakeTransport};
+use nodesty_api_library::{HttpResponse, VpsAction, VpsApiService};
+use reqwest::header::HeaderValue;
+use reqwest::Method;
+
+fn bad_gateway() -> HttpResponse {
+    json(502, r#"{"message": "upstream unavailable"}"#)
+}
+
+fn keys(transport: &FakeTransport) -> Vec<Option<HeaderValue>> {
+    transport
+        .requests()
+        .into_iter()
+        .map(|request| request.headers.get("idempotency-key").cloned())
+        .collect()
+}
+
+#[tokio::test]
+async fn post_retries_reuse_the_same_key() {
+    let transport = FakeTransport::new([
+        bad_gateway(),
+        bad_gateway(),
+        response(204, None, ""),
+        response(204, None, ""),
+    ]);
+    let vps = VpsApiService::new(client(&transport, options().with_retry(2)));
+
+    vps.perform_action("42", VpsAction::Restart).await.unwrap();
+    vps.perform_action("42", VpsAction::Restart).await.unwrap();
+
+    let requests = transport.requests();
+    assert!(requests.iter().all(|request| request.method == Method::POST));
+    let keys = keys(&transport);
+    assert_eq!(keys.len(), 4);
+    let first = keys[0].clone().expect("POSTs carry an idempotency key");
+    assert_eq!(keys[1].as_ref(), Some(&first));
+    assert_eq!(keys[2].as_ref(), Some(&first));
+    // A new call is a new operation and gets its own key.
+    assert!(keys[3].as_ref().is_some_and(|key| *key != first));
+}
+
+#[tokio::test]
+async fn explicit_key_is_sent_as_is() {
+    let transport = FakeTransport::new([bad_gateway(), response(204, None, "")]);
+    let vps = VpsApiService::new(client(&transport, options().with_retry(1)));
+
+    vps.perform_action("42", VpsAction::Stop)
+        .idempotency_key("job-17-stop")
+        .await
+        .unwrap();
+    let key = Some(HeaderValue::from_static("job-17-stop"));
+    assert_eq!(keys(&transport), [key.clone(), key]);
+}
+
+#[tokio::test]
+async fn posts_without_keys_are_not_retried() {
+    let transport = FakeTransport::new([bad_gateway()]);
+    let options = options().with_retry(2).with_idempotency_keys(false);
+    let vps = VpsApiService::new(client(&transport, options));
+
+    let error = vps.perform_action("42", VpsAction::Restart).await.unwrap_err();
+    assert_eq!(error.status().map(|status| status.as_u16()), Some(502));
+    assert_eq!(keys(&transport), [None]);
+}
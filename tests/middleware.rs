@@ -0,0 +1,101 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{builder, json, options, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::transport::BoxFuture;
+use nodesty_api_library::{
+    HttpRequest, HttpResponse, Middleware, Next, NodestyError, VpsApiService,
+};
+use reqwest::header::HeaderValue;
+use std::sync::{Arc, Mutex};
+
+/// Logs `name>` on the way in and `<name` on the way out, and tags the request with a header.
+struct Recording {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Middleware for Recording {
+    fn handle<'a>(
+        &'a self,
+        mut request: HttpRequest,
+        next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, NodestyError>> {
+        Box::pin(async move {
+            self.log.lock().unwrap().push(format!("{}>", self.name));
+            request.headers.append("x-middleware", HeaderValue::from_static(self.name));
+            let response = next.run(request).await;
+            self.log.lock().unwrap().push(format!("<{}", self.name));
+            response
+        })
+    }
+}
+
+/// Answers every request itself without calling the rest of the chain.
+struct Canned(&'static str);
+
+impl Middleware for Canned {
+    fn handle<'a>(
+        &'a self,
+        _request: HttpRequest,
+        _next: Next<'a>,
+    ) -> BoxFuture<'a, Result<HttpResponse, NodestyError>> {
+        Box::pin(async move { Ok(json(200, self.0)) })
+    }
+}
+
+#[tokio::test]
+async fn middlewares_run_in_the_order_they_were_added() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let transport = FakeTransport::new([json(200, OS_TEMPLATES)]);
+    let client = builder(&transport, options())
+        .middleware(Recording { name: "outer", log: log.clone() })
+        .middleware(Recording { name: "inner", log: log.clone() })
+        .build()
+        .unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    vps.get_os_templates("42").await.unwrap();
+    assert_eq!(*log.lock().unwrap(), ["outer>", "inner>", "<inner", "<outer"]);
+
+    let requests = transport.requests();
+    let tags: Vec<_> = requests[0].headers.get_all("x-middleware").iter().collect();
+    assert_eq!(tags, ["outer", "inner"]);
+}
+
+#[tokio::test]
+async fn middlewares_wrap_every_attempt() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let transport = FakeTransport::new([
+        json(502, r#"{"message": "upstream unavailable"}"#),
+        json(200, OS_TEMPLATES),
+    ]);
+    let client = builder(&transport, options().with_retry(1))
+        .middleware(Recording { name: "only", log: log.clone() })
+        .build()
+        .unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    vps.get_os_templates("42").await.unwrap();
+    assert_eq!(*log.lock().unwrap(), ["only>", "<only", "only>", "<only"]);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn middleware_can_answer_without_the_transport() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let transport = FakeTransport::default();
+    let client = builder(&transport, options())
+        .middleware(Recording { name: "outer", log: log.clone() })
+        .middleware(Canned(OS_TEMPLATES))
+        .middleware(Recording { name: "skipped", log: log.clone() })
+        .build()
+        .unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    let templates = vps.get_os_templates("42").await.unwrap().into_result().unwrap();
+    assert_eq!(templates.len(), 2);
+    assert_eq!(*log.lock().unwrap(), ["outer>", "<outer"]);
+    assert!(transport.requests().is_empty());
+}
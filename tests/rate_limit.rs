@@ -0,0 +1,51 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{client, json, options, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::{NodestyError, VpsApiService};
+use reqwest::header::{HeaderValue, RETRY_AFTER};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn waits_out_too_many_requests() {
+    let mut limited = json(429, r#"{"message": "slow down"}"#);
+    limited.headers.insert(RETRY_AFTER, HeaderValue::from_static("0.1"));
+    let transport = FakeTransport::new([limited, json(200, OS_TEMPLATES)]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    let started = Instant::now();
+    let templates = vps.get_os_templates("42").await.unwrap().into_result().unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    assert_eq!(templates.len(), 2);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn exhausted_window_holds_back_the_next_call() {
+    let mut last = json(200, OS_TEMPLATES);
+    last.headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+    last.headers.insert("x-ratelimit-reset", HeaderValue::from_static("0.1"));
+    let transport = FakeTransport::new([last, json(200, OS_TEMPLATES)]);
+    let vps = VpsApiService::new(client(&transport, options().with_rate_limit_offset_ms(0)));
+
+    vps.get_os_templates("1").await.unwrap();
+    let started = Instant::now();
+    vps.get_os_templates("2").await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(90));
+}
+
+#[tokio::test]
+async fn gives_up_on_too_many_requests_without_retries() {
+    let mut limited = json(429, r#"{"message": "slow down"}"#);
+    limited.headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+    let transport = FakeTransport::new([limited]);
+    let vps = VpsApiService::new(client(&transport, options().with_retry(0)));
+
+    match vps.get_os_templates("42").await {
+        Err(NodestyError::RateLimited { retry_after }) => {
+            assert_eq!(retry_after, Some(Duration::from_secs(7)));
+        }
+        other => panic!("expected RateLimited, got {:?}", other),
+    }
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{client, json, options, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::{
+    CancellationToken, NodestyApiClient, NodestyError, RequestOptions, VpsApiService,
+};
+use reqwest::header::{HeaderName, HeaderValue};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn per_call_settings_reach_the_transport() {
+    let transport = FakeTransport::new([json(200, OS_TEMPLATES), json(200, OS_TEMPLATES)]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    vps.get_os_templates("42")
+        .query("from", "2024-01-01")
+        .query("label", "a b&c")
+        .header(HeaderName::from_static("x-request-id"), HeaderValue::from_static("abc"))
+        .timeout(Duration::from_secs(5))
+        .await
+        .unwrap();
+    vps.get_os_templates("42").await.unwrap();
+
+    let requests = transport.requests();
+    assert!(
+        requests[0].url.ends_with("/services/42/vps/os-templates?from=2024-01-01&label=a%20b%26c"),
+        "{}",
+        requests[0].url
+    );
+    assert_eq!(requests[0].headers["x-request-id"], "abc");
+    assert_eq!(requests[0].timeout, Some(Duration::from_secs(5)));
+
+    assert!(requests[1].url.ends_with("/services/42/vps/os-templates"));
+    assert!(!requests[1].headers.contains_key("x-request-id"));
+    assert_eq!(requests[1].timeout, Some(Duration::from_secs(30)));
+}
+
+#[tokio::test]
+async fn request_options_apply_as_a_whole() {
+    let transport = FakeTransport::new([json(200, OS_TEMPLATES)]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    let options = RequestOptions::new()
+        .with_query("page", 2)
+        .with_timeout(Duration::from_millis(1500));
+    vps.get_os_templates("42").options(options).await.unwrap();
+
+    let request = &transport.requests()[0];
+    assert!(request.url.ends_with("/services/42/vps/os-templates?page=2"), "{}", request.url);
+    assert_eq!(request.timeout, Some(Duration::from_millis(1500)));
+}
+
+#[tokio::test]
+async fn cancelled_call_stops_waiting() {
+    // The transport never answers, so only cancellation can end the call.
+    let transport = FakeTransport::default();
+    let vps = VpsApiService::new(client(&transport, options()));
+    let token = CancellationToken::new();
+
+    let (result, ()) = tokio::join!(vps.get_os_templates("42").cancellation(token.clone()), async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        token.cancel();
+    });
+    assert!(matches!(result, Err(NodestyError::Cancelled)), "{:?}", result);
+    assert_eq!(transport.requests().len(), 1);
+
+    // A token that is already cancelled stops the call before anything is sent.
+    let result = vps.get_os_templates("42").cancellation(token).await;
+    assert!(matches!(result, Err(NodestyError::Cancelled)), "{:?}", result);
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn unanswered_request_times_out() {
+    // Accepts connections but never answers them.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let client = NodestyApiClient::new(options().with_base_url(base_url).with_retry(0)).unwrap();
+    let vps = VpsApiService::new(Arc::new(client));
+
+    let started = Instant::now();
+    let result = vps.get_os_templates("42").timeout(Duration::from_millis(100)).await;
+    assert!(matches!(result, Err(NodestyError::Timeout)), "{:?}", result);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
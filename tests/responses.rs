@@ -0,0 +1,54 @@
+#![cfg(all(feature = "vps", feature = "firewall"))]
+
+mod common;
+
+use common::{client, json, options, response, FakeTransport};
+use nodesty_api_library::{FirewallApiService, NodestyError, VpsAction, VpsApiService};
+use reqwest::{Method, StatusCode};
+
+#[tokio::test]
+async fn empty_no_content_decodes_to_unit() {
+    let transport = FakeTransport::new([response(204, None, "")]);
+    let firewall = FirewallApiService::new(client(&transport, options()));
+
+    let response = firewall.delete_rule("42", "203.0.113.7", 9).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.data, Some(()));
+
+    let requests = transport.requests();
+    assert_eq!(requests[0].method, Method::DELETE);
+    assert!(requests[0].url.ends_with("/services/42/firewall/203.0.113.7/rules/9"));
+}
+
+#[tokio::test]
+async fn acknowledged_action_decodes_to_unit() {
+    let body = r#"{"success": true, "message": "Action queued"}"#;
+    let transport = FakeTransport::new([json(200, body)]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    let response = vps.perform_action("42", VpsAction::Restart).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.error, None);
+    assert_eq!(response.data, Some(()));
+    assert_eq!(transport.requests()[0].method, Method::POST);
+}
+
+#[tokio::test]
+async fn html_error_page_is_unexpected_content() {
+    let page = "<html><body><h1>502 Bad Gateway</h1></body></html>";
+    let transport = FakeTransport::new([response(502, Some("text/html"), page)]);
+    let vps = VpsApiService::new(client(&transport, options().with_retry(0)));
+
+    match vps.get_details("42").await {
+        Err(NodestyError::UnexpectedContent {
+            status,
+            content_type,
+            snippet,
+        }) => {
+            assert_eq!(status, StatusCode::BAD_GATEWAY);
+            assert_eq!(content_type.as_deref(), Some("text/html"));
+            assert_eq!(snippet, page);
+        }
+        other => panic!("expected UnexpectedContent, got {:?}", other),
+    }
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "vps")]
+
+mod common;
+
+use common::{client, json, options, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::{NodestyError, RetryBudget, VpsAction, VpsApiService};
+use reqwest::Method;
+
+#[tokio::test]
+async fn retries_bad_gateway() {
+    let transport = FakeTransport::new([
+        json(502, r#"{"message": "upstream unavailable"}"#),
+        json(200, OS_TEMPLATES),
+    ]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    let templates = vps.get_os_templates("42").await.unwrap().into_result().unwrap();
+    assert_eq!(templates.len(), 2);
+    assert_eq!(templates[1].name, "Ubuntu 24.04");
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests.iter().all(|request| request.method == Method::GET));
+    assert!(requests[0].url.ends_with("/services/42/vps/os-templates"));
+}
+
+#[tokio::test]
+async fn gives_up_after_the_configured_retries() {
+    let transport = FakeTransport::new((0..3).map(|_| json(503, r#"{"message": "down"}"#)));
+    let vps = VpsApiService::new(client(&transport, options().with_retry(2)));
+
+    match vps.get_os_templates("42").await {
+        Err(NodestyError::Server { status, message }) => {
+            assert_eq!(status.as_u16(), 503);
+            assert_eq!(message.as_deref(), Some("down"));
+        }
+        other => panic!("expected Server, got {:?}", other),
+    }
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn empty_budget_stops_retries() {
+    let transport = FakeTransport::new([json(502, "{}"), json(200, OS_TEMPLATES)]);
+    let options = options().with_retry_budget(RetryBudget::new(0, 0.1));
+    let vps = VpsApiService::new(client(&transport, options));
+
+    assert!(vps.get_os_templates("42").await.is_err());
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let transport = FakeTransport::new([json(404, r#"{"message": "no such service"}"#)]);
+    let vps = VpsApiService::new(client(&transport, options()));
+
+    assert!(matches!(
+        vps.perform_action("42", VpsAction::Restart).await,
+        Err(NodestyError::NotFound { .. })
+    ));
+    assert_eq!(transport.requests().len(), 1);
+}
@@ -0,0 +1,42 @@
+#![cfg(all(feature = "vps", feature = "firewall"))]
+
+mod common;
+
+use common::{client, json, options, response, FakeTransport, OS_TEMPLATES};
+use nodesty_api_library::{FirewallApiService, FirewallCreateRuleData, VpsApiService};
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::Method;
+use serde_json::Value;
+
+#[tokio::test]
+async fn requests_go_through_the_transport() {
+    let transport = FakeTransport::new([json(200, OS_TEMPLATES), response(204, None, "")]);
+    let client = client(&transport, options());
+    let vps = VpsApiService::new(client.clone());
+    let firewall = FirewallApiService::new(client);
+
+    let templates = vps.get_os_templates("42").await.unwrap().into_result().unwrap();
+    assert_eq!(templates[0].name, "Debian 12");
+    firewall
+        .create_rule("42", "203.0.113.7", FirewallCreateRuleData { port: 25565, app_id: 3 })
+        .await
+        .unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+
+    let get = &requests[0];
+    assert_eq!(get.method, Method::GET);
+    assert!(get.url.ends_with("/services/42/vps/os-templates"), "{}", get.url);
+    assert_eq!(get.headers[AUTHORIZATION], "PAT test-token");
+    assert_eq!(get.headers[ACCEPT], "application/json");
+    assert!(get.body.is_none());
+
+    let post = &requests[1];
+    assert_eq!(post.method, Method::POST);
+    assert!(post.url.ends_with("/services/42/firewall/203.0.113.7/rules"), "{}", post.url);
+    assert_eq!(post.headers[AUTHORIZATION], "PAT test-token");
+    assert_eq!(post.headers[CONTENT_TYPE], "application/json");
+    let body: Value = serde_json::from_slice(post.body.as_deref().unwrap()).unwrap();
+    assert_eq!(body, serde_json::json!({"port": 25565, "appId": 3}));
+}